@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+use poise::serenity_prelude::{Context, async_trait};
+use regex::Regex;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::background_task::BackgroundTask;
+use crate::error::Error;
+use crate::types::Data;
+use crate::url::clean_url;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlocklistConfig {
+    /// File with one domain per line. Subdomains of a listed domain are blocked too.
+    pub domains_path: PathBuf,
+    /// File with one regex per line, matched against canonical URLs (without the scheme).
+    pub patterns_path: PathBuf,
+    /// How often the files are checked for changes.
+    pub reload_seconds: u64,
+    /// Channel where flagged submissions are logged for moderators.
+    pub log_channel_id: Option<u64>,
+}
+
+impl Default for BlocklistConfig {
+    fn default() -> Self {
+        Self {
+            domains_path: PathBuf::from("blocklist/domains.txt"),
+            patterns_path: PathBuf::from("blocklist/patterns.txt"),
+            reload_seconds: 60,
+            log_channel_id: None,
+        }
+    }
+}
+
+/// The blocklist entry a URL matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlocklistRule {
+    Domain(String),
+    Pattern(String),
+}
+
+impl fmt::Display for BlocklistRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Domain(domain) => write!(f, "domain `{domain}`"),
+            Self::Pattern(pattern) => write!(f, "pattern `{pattern}`"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    domains: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Blocklist {
+    /// Parses the domain and pattern files, skipping blank lines and `#` comments. Returns the
+    /// lines that couldn't be parsed alongside the blocklist.
+    pub fn parse(domains: &str, patterns: &str) -> (Self, Vec<String>) {
+        let mut invalid = Vec::new();
+
+        let domains = entries(domains)
+            .filter_map(|line| {
+                let domain = clean_url(&line.to_lowercase());
+                let domain = domain.split('/').next().unwrap_or_default();
+                if domain.contains('.') {
+                    Some(domain.to_owned())
+                } else {
+                    invalid.push(format!("domain `{line}`"));
+                    None
+                }
+            })
+            .collect();
+
+        let patterns = entries(patterns)
+            .filter_map(|line| match Regex::new(line) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    invalid.push(format!("pattern `{line}`: {e}"));
+                    None
+                }
+            })
+            .collect();
+
+        (Self { domains, patterns }, invalid)
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.domains.len() + self.patterns.len()
+    }
+
+    /// Returns the first rule matching the canonical `url`, checking domains first.
+    pub fn check(&self, url: &str) -> Option<BlocklistRule> {
+        let host = url.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+
+        let mut suffix = host;
+        loop {
+            if self.domains.contains(suffix) {
+                return Some(BlocklistRule::Domain(suffix.to_owned()));
+            }
+            match suffix.split_once('.') {
+                Some((_, rest)) if rest.contains('.') => suffix = rest,
+                _ => break,
+            }
+        }
+
+        self.patterns
+            .iter()
+            .find(|regex| regex.is_match(url))
+            .map(|regex| BlocklistRule::Pattern(regex.as_str().to_owned()))
+    }
+}
+
+fn entries(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Reads `path`, treating a missing file as empty.
+fn read_optional(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Holds the current [`Blocklist`], which [`BlocklistReloader`] swaps when its files change.
+pub struct BlocklistStore {
+    current: RwLock<Arc<Blocklist>>,
+}
+
+impl BlocklistStore {
+    pub fn new() -> Self {
+        Self {
+            current: RwLock::new(Arc::default()),
+        }
+    }
+
+    pub async fn get(&self) -> Arc<Blocklist> {
+        self.current.read().await.clone()
+    }
+
+    /// Loads the files in `config`, keeping the current blocklist if either can't be read.
+    /// Returns the number of rules loaded.
+    pub async fn reload(&self, config: &BlocklistConfig) -> Result<usize, Error> {
+        let domains = read_optional(&config.domains_path)?;
+        let patterns = read_optional(&config.patterns_path)?;
+
+        let (blocklist, invalid) = Blocklist::parse(&domains, &patterns);
+        for entry in invalid {
+            warn!("Skipping invalid blocklist {entry}");
+        }
+
+        let count = blocklist.rule_count();
+        *self.current.write().await = Arc::new(blocklist);
+
+        Ok(count)
+    }
+}
+
+impl Default for BlocklistStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reloads [`BlocklistStore`] whenever the blocklist files are modified, added or removed.
+pub struct BlocklistReloader {
+    ctx: Context,
+    last_modified: Option<[Option<SystemTime>; 2]>,
+}
+
+impl BlocklistReloader {
+    pub fn new(ctx: Context) -> Self {
+        Self {
+            ctx,
+            last_modified: None,
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for BlocklistReloader {
+    async fn init(ctx: Context) -> Result<Self, Error> {
+        Ok(Self::new(ctx))
+    }
+
+    fn interval(&mut self) -> Duration {
+        Duration::from_secs(
+            self.ctx
+                .data_ref::<Data>()
+                .config()
+                .blocklist
+                .reload_seconds,
+        )
+    }
+
+    async fn run(&mut self) {
+        let data = self.ctx.data_ref::<Data>();
+        let config = data.config();
+        let config = &config.blocklist;
+
+        let mtimes = [
+            modified(&config.domains_path),
+            modified(&config.patterns_path),
+        ];
+        if self.last_modified == Some(mtimes) {
+            return;
+        }
+
+        match data.blocklist.reload(config).await {
+            Ok(count) => {
+                info!("Loaded {count} blocklist rules");
+                self.last_modified = Some(mtimes);
+            }
+            Err(e) => warn!("Failed to load blocklist: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries_and_reports_invalid_lines() {
+        let (blocklist, invalid) = Blocklist::parse(
+            "# fake sites\nhttps://www.Bad.com/\n\nlocalhost\n",
+            "^free-[a-z]+\\.net/\n(unclosed\n",
+        );
+        assert_eq!(blocklist.rule_count(), 2);
+        assert_eq!(invalid.len(), 2);
+        assert!(invalid[0].contains("localhost"));
+        assert!(invalid[1].contains("(unclosed"));
+    }
+
+    #[test]
+    fn matches_domains_and_subdomains() {
+        let (blocklist, _) = Blocklist::parse("bad.com\nsub.evil.org", "");
+        assert_eq!(
+            blocklist.check("bad.com/download"),
+            Some(BlocklistRule::Domain("bad.com".to_owned()))
+        );
+        assert_eq!(
+            blocklist.check("cdn.bad.com:8080"),
+            Some(BlocklistRule::Domain("bad.com".to_owned()))
+        );
+        assert_eq!(
+            blocklist.check("a.sub.evil.org"),
+            Some(BlocklistRule::Domain("sub.evil.org".to_owned()))
+        );
+        assert_eq!(blocklist.check("evil.org"), None);
+        assert_eq!(blocklist.check("notbad.com"), None);
+    }
+
+    #[test]
+    fn matches_patterns() {
+        let (blocklist, _) = Blocklist::parse("", r"^free-[a-z]+\.net/");
+        assert_eq!(
+            blocklist.check("free-movies.net/watch"),
+            Some(BlocklistRule::Pattern(r"^free-[a-z]+\.net/".to_owned()))
+        );
+        assert_eq!(blocklist.check("free-movies.net"), None);
+    }
+}
@@ -1,22 +1,29 @@
-use std::borrow::Cow;
-
 use poise::serenity_prelude::{
     Channel, Color, CreateAllowedMentions, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
-    CreateMessage, CreateThread, Message, MessageReference, Reaction, Timestamp, prelude::*,
+    CreateMessage, CreateThread, GenericChannelId, GuildThread, Message, MessageId,
+    MessageReference, Reaction, Timestamp, prelude::*,
 };
-use sea_orm::{ActiveValue::*, Iterable, prelude::*};
+use sea_orm::{Iterable, prelude::*};
 
-use crate::constants::{AUTO_THREAD_CHANNELS, FmhyChannel, FmhyServerRole};
-use crate::db::{get_wiki_urls_by_urls, infer_wiki_url_status, update_wiki_urls_with_message};
-use crate::entities::enums::WikiUrlStatus;
+use crate::db::{
+    delete_pending_wiki_urls, get_same_site_wiki_urls, get_wiki_urls_by_urls,
+    infer_wiki_url_status, insert_wiki_urls_from_message, record_wiki_url_events,
+    update_wiki_urls_with_message, wiki_url_event_from_message,
+};
+use crate::entities::enums::{GuildSetting, RemovalCategory, WikiUrlStatus};
 use crate::entities::{prelude::*, wiki_urls};
+use crate::extractors::MessageSource;
 use crate::formatters::UrlFormatter;
-use crate::message::get_content_or_referenced;
+use crate::guild_config::GuildConfig;
+use crate::message::RemovalReason;
 use crate::types::Data;
 use crate::url::extract_urls;
 
 pub async fn on_message(ctx: &Context, message: &Message) {
-    for channel_id in AUTO_THREAD_CHANNELS {
+    let data = ctx.data_ref::<Data>();
+    let config = data.guild_config.get(message.guild_id, &data.pool).await;
+
+    for channel_id in config.ids(GuildSetting::AutoThread) {
         if message.channel_id.get() == *channel_id {
             let _ = message
                 .channel_id
@@ -36,16 +43,19 @@ pub async fn on_message(ctx: &Context, message: &Message) {
         return;
     }
 
-    let Some(m_content) = get_content_or_referenced(&ctx.http, message)
-        .await
-        .or_else(|| {
-            message
-                .embeds
-                .first()
-                .and_then(|e| e.fields.iter().find(|f| f.name == "Message"))
-                .map(|f| Cow::Borrowed(f.value.as_str()))
-        })
-    else {
+    let status = infer_wiki_url_status(&config, message.channel_id.get());
+    let thread = if status.is_none() && !config.is(GuildSetting::Feedback, message.channel_id.get())
+    {
+        message_thread(ctx, message).await
+    } else {
+        None
+    };
+    // Only fetch forwards and attachments for messages the bot may act on.
+    if !is_submission_context(&config, message, status, thread.as_ref()) {
+        return;
+    }
+
+    let Some(m_content) = MessageSource::fetch(&ctx.http, message).await.text() else {
         return;
     };
 
@@ -53,7 +63,17 @@ pub async fn on_message(ctx: &Context, message: &Message) {
         return;
     };
 
-    let status = infer_wiki_url_status(message.channel_id.get());
+    if status.is_none_or(|s| s == WikiUrlStatus::Pending) {
+        send_unsafe_warning(ctx, message, &urls).await;
+    }
+    let removal = (status == Some(WikiUrlStatus::Removed)).then(|| {
+        RemovalReason::parse(
+            &m_content,
+            config
+                .is(GuildSetting::DeadSites, message.channel_id.get())
+                .then_some(RemovalCategory::Dead),
+        )
+    });
 
     if let Some(entries) = get_wiki_urls_by_urls(&urls, &ctx.data::<Data>().pool).await {
         if !entries.is_empty() {
@@ -63,21 +83,21 @@ pub async fn on_message(ctx: &Context, message: &Message) {
                         entries,
                         message,
                         status.unwrap(),
+                        removal.as_ref(),
                         &ctx.data::<Data>().pool,
                     )
                     .await;
                 }
                 Some(WikiUrlStatus::Pending) | None => {
-                    if status.is_none()
-                        && let Ok(Channel::GuildThread(thread)) = message.channel(&ctx.http).await
-                        && thread.parent_id.get() == FmhyChannel::LINK_TESTING
+                    if let Some(thread) = &thread
+                        && config.is(GuildSetting::LinkTesting, thread.parent_id.get())
                         && thread.total_message_sent == 0
                         && !entries.iter().any(|e| {
                             e.status != WikiUrlStatus::Pending
                                 || !e.channel_id.is_some_and(|cid| {
-                                    matches!(
+                                    config.is_any(
+                                        &[GuildSetting::AddLinks, GuildSetting::NsfwAddLinks],
                                         cid as u64,
-                                        FmhyChannel::ADD_LINKS | FmhyChannel::NSFW_ADD_LINKS
                                     )
                                 })
                         })
@@ -86,72 +106,244 @@ pub async fn on_message(ctx: &Context, message: &Message) {
                             entries,
                             message,
                             WikiUrlStatus::Pending,
+                            None,
                             &ctx.data::<Data>().pool,
                         )
                         .await;
                         return;
                     }
 
-                    let should_proceed = status.is_some()
-                        || message.channel_id.get() == FmhyChannel::FEEDBACK
-                        || matches!(
-                            message.channel(&ctx.http).await,
-                            Ok(Channel::GuildThread(thread))
-                                if matches!(
-                                    thread.parent_id.get(),
-                                    FmhyChannel::ADD_LINKS
-                                        | FmhyChannel::NSFW_ADD_LINKS
-                                        | FmhyChannel::LINK_TESTING
-                                )
-                        );
-
-                    if !should_proceed {
-                        return;
-                    }
-
-                    let mut embed = CreateEmbed::new().title("Warning").color(Color::ORANGE);
-
-                    for status in WikiUrlStatus::iter() {
-                        if let Some(formatted) = entries.format_for_embed(&status) {
-                            let title = match status {
-                                WikiUrlStatus::Added => "Links already in the wiki:",
-                                WikiUrlStatus::Pending => "Links already in queue:",
-                                WikiUrlStatus::Removed => "Links previously removed from the wiki:",
-                            };
-                            embed = embed.field(title, formatted, false);
-                        }
-                    }
+                    let same_site = get_same_site_wiki_urls(&urls, &ctx.data::<Data>().pool).await;
+                    send_duplicate_warning(ctx, &config, message, &entries, &same_site).await;
+                }
+            }
+        } else {
+            if let Some(status) = status {
+                insert_wiki_urls_from_message(
+                    &urls,
+                    message,
+                    status,
+                    removal.as_ref(),
+                    &ctx.data::<Data>().pool,
+                )
+                .await;
+            }
 
-                    if let Ok(m) = message
-                        .channel_id
-                        .send_message(
-                            &ctx.http,
-                            CreateMessage::new()
-                                .add_embed(embed)
-                                .reference_message(MessageReference::from(message))
-                                .allowed_mentions(CreateAllowedMentions::new().replied_user(true)),
-                        )
-                        .await
-                        && message.channel_id.get() != FmhyChannel::FEEDBACK
-                    {
-                        let _ = m.react(&ctx.http, '❌').await;
-                    }
+            if status.is_none_or(|s| s == WikiUrlStatus::Pending) {
+                let same_site = get_same_site_wiki_urls(&urls, &ctx.data::<Data>().pool).await;
+                if !same_site.is_empty() {
+                    send_duplicate_warning(ctx, &config, message, &entries, &same_site).await;
                 }
             }
-        } else if let Some(status) = status {
-            let _ = WikiUrls::insert_many(urls.into_iter().map(|url| wiki_urls::ActiveModel {
-                url: Set(url),
-                user_id: Set(Some(message.author.id.get() as i64)),
-                guild_id: Set(message.guild_id.map(|g| g.get() as i64)),
-                channel_id: Set(Some(message.channel_id.get() as i64)),
-                message_id: Set(Some(message.id.get() as i64)),
-                status: Set(status),
-                ..Default::default()
-            }))
-            .exec(&ctx.data::<Data>().pool)
+        }
+    }
+}
+
+/// Brings the pending entries submitted in an edited `message` in line with its new content.
+pub async fn on_message_update(ctx: &Context, message: &Message) {
+    if message.author.bot() && message.webhook_id.is_none() {
+        return;
+    }
+
+    let data = ctx.data_ref::<Data>();
+    let Ok(tracked) = WikiUrls::find()
+        .filter(wiki_urls::Column::MessageId.eq(message.id.get() as i64))
+        .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Pending))
+        .all(&data.pool)
+        .await
+    else {
+        return;
+    };
+
+    let config = data.guild_config.get(message.guild_id, &data.pool).await;
+    let is_submission =
+        infer_wiki_url_status(&config, message.channel_id.get()) == Some(WikiUrlStatus::Pending);
+    if tracked.is_empty() && !is_submission {
+        return;
+    }
+
+    let urls = match MessageSource::fetch(&ctx.http, message).await.text() {
+        Some(content) => extract_urls(&content).unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let dropped: Vec<_> = tracked
+        .into_iter()
+        .filter(|entry| !urls.contains(&entry.url))
+        .collect();
+    if !dropped.is_empty()
+        && WikiUrls::delete_many()
+            .filter(wiki_urls::Column::Id.is_in(dropped.iter().map(|e| e.id)))
+            .exec(&data.pool)
+            .await
+            .is_ok()
+    {
+        let events = dropped
+            .into_iter()
+            .map(|entry| wiki_url_event_from_message(entry.url, Some(entry.status), None, message))
+            .collect();
+        let _ = record_wiki_url_events(events, &data.pool).await;
+    }
+
+    if is_submission {
+        insert_wiki_urls_from_message(&urls, message, WikiUrlStatus::Pending, None, &data.pool)
             .await;
+    }
+}
+
+/// Stops tracking pending links whose submission message was deleted.
+pub async fn on_message_delete(ctx: &Context, message_ids: &[MessageId]) {
+    let message_ids: Vec<i64> = message_ids.iter().map(|id| id.get() as i64).collect();
+    delete_pending_wiki_urls(&message_ids, &ctx.data_ref::<Data>().pool).await;
+}
+
+/// Returns the thread `message` was posted in, if any. The guild cache settles this for most
+/// messages, so the channel is only fetched when it isn't cached.
+async fn message_thread(ctx: &Context, message: &Message) -> Option<GuildThread> {
+    let guild_id = message.guild_id?;
+    let channel_id = message.channel_id.get();
+
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        if let Some(thread) = guild.threads.iter().find(|t| t.id.get() == channel_id) {
+            return Some(thread.clone());
+        }
+        if guild.channels.iter().any(|c| c.id.get() == channel_id) {
+            return None;
+        }
+    }
+
+    match message.channel(&ctx.http).await {
+        Ok(Channel::GuildThread(thread)) => Some(thread),
+        _ => None,
+    }
+}
+
+/// Whether `message` was posted somewhere links are tracked, submitted or discussed for the wiki.
+/// `thread` is the thread the message was posted in, if any.
+fn is_submission_context(
+    config: &GuildConfig,
+    message: &Message,
+    status: Option<WikiUrlStatus>,
+    thread: Option<&GuildThread>,
+) -> bool {
+    status.is_some()
+        || config.is(GuildSetting::Feedback, message.channel_id.get())
+        || thread.is_some_and(|thread| {
+            config.is_any(
+                &[
+                    GuildSetting::AddLinks,
+                    GuildSetting::NsfwAddLinks,
+                    GuildSetting::LinkTesting,
+                ],
+                thread.parent_id.get(),
+            )
+        })
+}
+
+async fn send_duplicate_warning(
+    ctx: &Context,
+    config: &GuildConfig,
+    message: &Message,
+    entries: &[wiki_urls::Model],
+    same_site: &[wiki_urls::Model],
+) {
+    let mut embed = CreateEmbed::new().title("Warning").color(Color::ORANGE);
+
+    for status in WikiUrlStatus::iter() {
+        if let Some(formatted) = entries.format_for_embed(&status) {
+            let title = match status {
+                WikiUrlStatus::Added => "Links already in the wiki:",
+                WikiUrlStatus::Pending => "Links already in queue:",
+                WikiUrlStatus::Removed => "Links previously removed from the wiki:",
+            };
+            embed = embed.field(title, formatted, false);
         }
     }
+
+    if let Some(formatted) = same_site.format_with_status(10) {
+        embed = embed.field("Same site already tracked:", formatted, false);
+    }
+
+    if let Ok(snapshot) = ctx.data_ref::<Data>().wiki.get().await
+        && let Some(formatted) = snapshot
+            .index
+            .format_locations(entries.iter().chain(same_site).map(|e| e.url.as_str()), 5)
+    {
+        embed = embed.field("Found in the wiki under:", formatted, false);
+    }
+
+    if let Ok(m) = message
+        .channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .add_embed(embed)
+                .reference_message(MessageReference::from(message))
+                .allowed_mentions(CreateAllowedMentions::new().replied_user(true)),
+        )
+        .await
+        && !config.is(GuildSetting::Feedback, message.channel_id.get())
+    {
+        let _ = m.react(&ctx.http, '❌').await;
+    }
+}
+
+/// Replies to `message` when any of `urls` is on the blocklist, and logs it for moderators.
+async fn send_unsafe_warning(ctx: &Context, message: &Message, urls: &[String]) {
+    let data = ctx.data_ref::<Data>();
+    let blocklist = data.blocklist.get().await;
+    let hits: Vec<String> = urls
+        .iter()
+        .filter_map(|url| {
+            blocklist
+                .check(url)
+                .map(|rule| format!("- {url} (matched {rule})"))
+        })
+        .collect();
+    if hits.is_empty() {
+        return;
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Flagged as unsafe")
+        .description(hits.join("\n"))
+        .footer(CreateEmbedFooter::new(
+            "These links match the unsafe sites blocklist. Please don't submit them.",
+        ))
+        .color(Color::RED);
+
+    let _ = message
+        .channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .add_embed(embed.clone())
+                .reference_message(MessageReference::from(message))
+                .allowed_mentions(CreateAllowedMentions::new().replied_user(true)),
+        )
+        .await;
+
+    if let Some(channel_id) = data.config().blocklist.log_channel_id {
+        let log = embed
+            .author(
+                CreateEmbedAuthor::new(&message.author.name).icon_url(
+                    message
+                        .author
+                        .avatar_url()
+                        .unwrap_or_else(|| message.author.default_avatar_url()),
+                ),
+            )
+            .field("Author", format!("<@{}>", message.author.id), true)
+            .field("Message", message.link(), true)
+            .footer(CreateEmbedFooter::new(format!(
+                "User ID: {}",
+                message.author.id
+            )))
+            .timestamp(message.timestamp);
+        let _ = GenericChannelId::new(channel_id)
+            .send_message(&ctx.http, CreateMessage::new().add_embed(log))
+            .await;
+    }
 }
 
 pub async fn on_reaction_add(ctx: &Context, reaction: &Reaction) {
@@ -213,21 +405,23 @@ pub async fn on_reaction_add(ctx: &Context, reaction: &Reaction) {
         let _ = message.delete(&ctx.http, None).await;
     }
 
+    let data = ctx.data_ref::<Data>();
+    let config = data.guild_config.get(reaction.guild_id, &data.pool).await;
+
     if is_delete
         && let Some(member) = reaction.member.as_ref()
         && message
             .reactions
             .iter()
             .any(|m| m.me && m.reaction_type == '❌'.into())
-        && (member.roles.iter().any(|r| {
-            matches!(
-                r.get(),
-                FmhyServerRole::FIRST_MATE | FmhyServerRole::CELESTIAL | FmhyServerRole::CAPTAIN
-            )
-        }) || message
-            .referenced_message
-            .as_ref()
-            .is_some_and(|m| m.author.id == user.id))
+        && (member
+            .roles
+            .iter()
+            .any(|r| config.is(GuildSetting::ModeratorRole, r.get()))
+            || message
+                .referenced_message
+                .as_ref()
+                .is_some_and(|m| m.author.id == user.id))
     {
         let _ = message.delete(&ctx.http, None).await;
 
@@ -1,12 +1,26 @@
 use std::collections::HashSet;
+use std::time::Duration;
 
-use poise::serenity_prelude::{CreateMessage, GuildThread, prelude::*};
+use poise::serenity_prelude::audit_log::{Action, Change, ThreadAction};
+use poise::serenity_prelude::{
+    CreateMessage, ForumTagId, GuildThread, Timestamp, UserId, prelude::*,
+};
 
-use crate::constants::FmhyChannel;
-use crate::constants::link_testing::ForumTag;
+use crate::db::update_wiki_urls_from_thread;
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::types::Data;
+
+/// Discord may write the audit log entry after sending the event, so the lookup is retried.
+const EDITOR_LOOKUP_ATTEMPTS: u32 = 3;
+const EDITOR_LOOKUP_DELAY: Duration = Duration::from_secs(2);
+/// Audit log entries older than this can't be the tag change that triggered the event.
+const EDITOR_LOOKUP_MAX_AGE_SECONDS: i64 = 60;
 
 pub async fn on_thread_update(ctx: &Context, old: Option<&GuildThread>, new: &GuildThread) {
-    if new.parent_id.get() != FmhyChannel::LINK_TESTING {
+    let data = ctx.data_ref::<Data>();
+    let config = data.guild_config.get(Some(new.guild_id), &data.pool).await;
+
+    if !config.is(GuildSetting::LinkTesting, new.parent_id.get()) {
         return;
     }
 
@@ -22,20 +36,33 @@ pub async fn on_thread_update(ctx: &Context, old: Option<&GuildThread>, new: &Gu
     }
 
     let owner = new.owner_id.mention();
+    let mut status = None;
 
     for (tags, closing) in [
         (new_tags.difference(&old_tags), true),
         (old_tags.difference(&new_tags), false),
     ] {
         for tag in tags {
-            let content = match (tag.get(), closing) {
-                (ForumTag::REJECTED, true) => Some(format!("{owner}: thread closed as rejected.")),
-                (ForumTag::ADDED, true) => Some(format!(
-                    "{owner}: thread closed as approved; links will be added to the wiki."
-                )),
-                (ForumTag::REJECTED, false) => Some(format!(
-                    "{owner}: your previously rejected thread has been reopened; feel free to continue discussing and defending the links you were testing."
-                )),
+            let rejected = config.is(GuildSetting::RejectedTag, tag.get());
+            let added = config.is(GuildSetting::AddedTag, tag.get());
+
+            let content = match (rejected, added, closing) {
+                (true, _, true) => {
+                    status = Some(WikiUrlStatus::Removed);
+                    Some(format!("{owner}: thread closed as rejected."))
+                }
+                (_, true, true) => {
+                    status = Some(WikiUrlStatus::Added);
+                    Some(format!(
+                        "{owner}: thread closed as approved; links will be added to the wiki."
+                    ))
+                }
+                (true, _, false) => {
+                    status.get_or_insert(WikiUrlStatus::Pending);
+                    Some(format!(
+                        "{owner}: your previously rejected thread has been reopened; feel free to continue discussing and defending the links you were testing."
+                    ))
+                }
                 _ => None,
             };
             if let Some(content) = content {
@@ -45,4 +72,62 @@ pub async fn on_thread_update(ctx: &Context, old: Option<&GuildThread>, new: &Gu
             }
         }
     }
+
+    if let Some(status) = status {
+        let moderator = find_tag_editor(ctx, new, &new_tags).await;
+        update_wiki_urls_from_thread(new, status, moderator, &data.pool).await;
+    }
+}
+
+/// Looks up the moderator who just set the tags of `thread` to `tags` in the audit log. Returns
+/// `None` rather than guessing if no matching entry shows up.
+async fn find_tag_editor(
+    ctx: &Context,
+    thread: &GuildThread,
+    tags: &HashSet<ForumTagId>,
+) -> Option<UserId> {
+    for attempt in 0..EDITOR_LOOKUP_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(EDITOR_LOOKUP_DELAY).await;
+        }
+
+        let Ok(logs) = thread
+            .guild_id
+            .audit_logs(
+                &ctx.http,
+                Some(Action::Thread(ThreadAction::Update)),
+                None,
+                None,
+                None,
+            )
+            .await
+        else {
+            continue;
+        };
+
+        let now = Timestamp::now().unix_timestamp();
+        let editor = logs
+            .entries
+            .into_iter()
+            .find(|entry| {
+                entry
+                    .target_id
+                    .is_some_and(|id| id.get() == thread.id.get())
+                    && now - entry.id.created_at().unix_timestamp() <= EDITOR_LOOKUP_MAX_AGE_SECONDS
+                    && entry.changes.iter().flatten().any(|change| {
+                        matches!(
+                            change,
+                            Change::AppliedTags { new: Some(new), .. }
+                                if new.iter().copied().collect::<HashSet<_>>() == *tags
+                        )
+                    })
+            })
+            .map(|entry| entry.user_id);
+
+        if editor.is_some() {
+            return editor;
+        }
+    }
+
+    None
 }
@@ -0,0 +1,205 @@
+use poise::serenity_prelude::{Color, CreateEmbed, CreateEmbedFooter};
+use poise::{ChoiceParameter, CreateReply};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue::*, Iterable, prelude::*};
+
+use super::{Command, Context, Error};
+use crate::entities::enums::GuildSetting;
+use crate::entities::{guild_settings, prelude::*};
+
+/// Parses a channel or role mention, or a raw ID.
+fn parse_target(target: &str) -> Option<u64> {
+    target
+        .trim()
+        .trim_start_matches("<#")
+        .trim_start_matches("<@&")
+        .trim_end_matches('>')
+        .parse()
+        .ok()
+}
+
+#[poise::command(
+    slash_command,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    required_permissions = "ADMINISTRATOR",
+    subcommands("show", "add", "remove", "reset"),
+    subcommand_required
+)]
+async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows the channels, roles and forum tags the bot uses in this server
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let config = ctx
+        .data()
+        .guild_config
+        .get(ctx.guild_id(), &ctx.data().pool)
+        .await;
+
+    let mut embed = CreateEmbed::new()
+        .title("Server configuration")
+        .footer(CreateEmbedFooter::new(
+            "Settings marked (default) use the FMHY server's IDs",
+        ))
+        .color(Color::BLURPLE);
+
+    for setting in GuildSetting::iter() {
+        let mut value = config
+            .ids(setting)
+            .iter()
+            .map(|&id| setting.mention(id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if value.is_empty() {
+            value.push_str("None");
+        }
+        if !config.is_configured(setting) {
+            value.push_str(" (default)");
+        }
+        embed = embed.field(setting.name(), value, true);
+    }
+
+    ctx.send(CreateReply::new().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Maps a setting to a channel, role or forum tag, replacing the default
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn add(
+    ctx: Context<'_>,
+    #[description = "The setting to change"] setting: GuildSetting,
+    #[description = "A channel or role mention, or an ID"] target: String,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let Some(target_id) = parse_target(&target) else {
+        ctx.send(
+            CreateReply::new()
+                .content("Invalid target, expected a mention or an ID.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let inserted = GuildSettings::insert_many([guild_settings::ActiveModel {
+        guild_id: Set(guild_id.get() as i64),
+        setting: Set(setting),
+        target_id: Set(target_id as i64),
+        ..Default::default()
+    }])
+    .on_conflict(
+        OnConflict::columns([
+            guild_settings::Column::GuildId,
+            guild_settings::Column::Setting,
+            guild_settings::Column::TargetId,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_with_returning(&ctx.data().pool)
+    .await?;
+    ctx.data().guild_config.invalidate(guild_id).await;
+
+    let content = if inserted.is_empty() {
+        format!(
+            "{} is already set to {}.",
+            setting.name(),
+            setting.mention(target_id)
+        )
+    } else {
+        format!(
+            "Added {} to {}.",
+            setting.mention(target_id),
+            setting.name()
+        )
+    };
+    ctx.send(CreateReply::new().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Unmaps a channel, role or forum tag from a setting
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "The setting to change"] setting: GuildSetting,
+    #[description = "A channel or role mention, or an ID"] target: String,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let Some(target_id) = parse_target(&target) else {
+        ctx.send(
+            CreateReply::new()
+                .content("Invalid target, expected a mention or an ID.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let result = GuildSettings::delete_many()
+        .filter(guild_settings::Column::GuildId.eq(guild_id.get() as i64))
+        .filter(guild_settings::Column::Setting.eq(setting))
+        .filter(guild_settings::Column::TargetId.eq(target_id as i64))
+        .exec(&ctx.data().pool)
+        .await?;
+    ctx.data().guild_config.invalidate(guild_id).await;
+
+    let content = if result.rows_affected == 0 {
+        format!(
+            "{} isn't set to {}.",
+            setting.name(),
+            setting.mention(target_id)
+        )
+    } else {
+        format!(
+            "Removed {} from {}.",
+            setting.mention(target_id),
+            setting.name()
+        )
+    };
+    ctx.send(CreateReply::new().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Resets a setting to the FMHY server's default
+#[poise::command(slash_command, required_permissions = "ADMINISTRATOR")]
+async fn reset(
+    ctx: Context<'_>,
+    #[description = "The setting to reset"] setting: GuildSetting,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    GuildSettings::delete_many()
+        .filter(guild_settings::Column::GuildId.eq(guild_id.get() as i64))
+        .filter(guild_settings::Column::Setting.eq(setting))
+        .exec(&ctx.data().pool)
+        .await?;
+    ctx.data().guild_config.invalidate(guild_id).await;
+
+    ctx.send(
+        CreateReply::new()
+            .content(format!("Reset {} to the default.", setting.name()))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [config()]
+}
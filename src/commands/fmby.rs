@@ -1,24 +1,30 @@
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use futures::StreamExt;
 use poise::CreateReply;
 use poise::serenity_prelude::{
-    ActivityData, AutocompleteChoice, Color, CreateAutocompleteResponse, CreateEmbed,
-    CreateEmbedFooter, CreateMessage, EditMessage, GenericChannelId, OnlineStatus, futures,
+    ActivityData, AutocompleteChoice, ButtonStyle, Color, ComponentInteractionCollector,
+    ComponentInteractionDataKind, CreateActionRow, CreateAutocompleteResponse, CreateButton,
+    CreateComponent, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, EditMessage, GenericChannelId, OnlineStatus, Timestamp, futures,
 };
 use sea_orm::sea_query::OnConflict;
 use sea_orm::sqlx::types::chrono::Utc;
 use sea_orm::{ActiveValue::*, QueryOrder, QuerySelect, QueryTrait, TransactionTrait, prelude::*};
 
 use super::{Command, Context, Error};
-use crate::constants::{FMHY_SINGLE_PAGE_ENDPOINT, FmhyChannel};
-use crate::db::{ChunkSize, infer_wiki_url_status};
-use crate::entities::enums::WikiUrlStatus;
-use crate::entities::{prelude::*, wiki_urls};
-use crate::message::get_content_or_referenced;
+use crate::db::{ChunkSize, infer_wiki_url_status, record_wiki_url_events};
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
+use crate::extractors::MessageSource;
+use crate::formatters::{TimelineFormatter, format_removal_reason};
+use crate::types::Data;
 use crate::url::{clean_url, extract_urls};
-use crate::wiki::collect_wiki_urls;
+use crate::wiki::{
+    EntryGuildConfigs, SearchResult, WikiSnapshot, diff_wiki_urls, search_wiki, settled_before,
+};
 
 #[poise::command(
     slash_command,
@@ -106,30 +112,35 @@ async fn migrate(
     only_wiki: bool,
 ) -> Result<(), Error> {
     let start = Instant::now();
-    let content = reqwest::get(FMHY_SINGLE_PAGE_ENDPOINT)
-        .await?
-        .text()
-        .await?;
-    let urls = collect_wiki_urls(&content)
-        .iter()
-        .map(|url| clean_url(url).to_owned())
-        .collect::<Vec<_>>();
-    drop(content);
+    let snapshot = ctx.data().wiki.get().await?;
+    let urls = &snapshot.urls;
 
     let mut messages_processed = 0;
     let mut messages_skipped = 0;
     let mut urls_processed = 0;
     let mut entries = HashMap::new();
-    let channel_ids = [
-        FmhyChannel::RECENTLY_ADDED,
-        FmhyChannel::NSFW_RECENTLY_ADDED,
-        FmhyChannel::ADD_LINKS,
-        FmhyChannel::NSFW_ADD_LINKS,
-        FmhyChannel::DEAD_SITES,
-        FmhyChannel::REMOVE_SITES,
-        FmhyChannel::NSFW_REMOVED,
-    ];
-    ctx.say("Starting migration...").await?;
+    let config = ctx
+        .data()
+        .guild_config
+        .get(ctx.guild_id(), &ctx.data().pool)
+        .await;
+    let channel_ids: Vec<u64> = [
+        GuildSetting::RecentlyAdded,
+        GuildSetting::NsfwRecentlyAdded,
+        GuildSetting::AddLinks,
+        GuildSetting::NsfwAddLinks,
+        GuildSetting::DeadSites,
+        GuildSetting::RemoveSites,
+        GuildSetting::NsfwRemoved,
+    ]
+    .into_iter()
+    .flat_map(|setting| config.ids(setting).iter().copied())
+    .collect();
+    ctx.say(format!(
+        "Starting migration using {}...",
+        snapshot.describe()
+    ))
+    .await?;
     let mut reply = ctx.channel_id().say(ctx.http(), "Processing...").await?;
 
     if !only_wiki {
@@ -138,7 +149,7 @@ async fn migrate(
                 .messages_iter(ctx.http())
                 .boxed();
 
-            let Some(status) = infer_wiki_url_status(channel_id) else {
+            let Some(status) = infer_wiki_url_status(&config, channel_id) else {
                 continue;
             };
 
@@ -149,7 +160,8 @@ async fn migrate(
 
                 messages_processed += 1;
 
-                let Some(m_content) = get_content_or_referenced(ctx.http(), &message).await else {
+                let Some(m_content) = MessageSource::fetch(ctx.http(), &message).await.text()
+                else {
                     continue;
                 };
 
@@ -194,6 +206,7 @@ async fn migrate(
                         .or_insert_with(|| wiki_urls::ActiveModel {
                             url: Set(url),
                             user_id: Set(Some(message.author.id.get() as i64)),
+                            submitted_by: Set(Some(message.author.id.get() as i64)),
                             message_id: Set(Some(message.id.get() as i64)),
                             channel_id: Set(Some(message.channel_id.get() as i64)),
                             guild_id: Set(ctx.guild_id().map(|g| g.get() as i64)),
@@ -243,7 +256,7 @@ async fn migrate(
         }
     }
 
-    for url in urls {
+    for url in urls.iter().cloned() {
         entries
             .entry(url.clone())
             .or_insert_with(|| wiki_urls::ActiveModel {
@@ -269,14 +282,31 @@ async fn migrate(
         let chunk: Vec<_> = entries.drain(..chunk_size.min(entries.len())).collect();
         let txn = ctx.data().pool.begin().await?;
 
-        let _ = WikiUrls::insert_many(chunk)
+        if let Ok(inserted) = WikiUrls::insert_many(chunk)
             .on_conflict(
                 OnConflict::column(wiki_urls::Column::Url)
                     .do_nothing()
                     .to_owned(),
             )
-            .exec(&txn)
-            .await;
+            .exec_with_returning(&txn)
+            .await
+        {
+            let events = inserted
+                .into_iter()
+                .map(|entry| wiki_url_events::ActiveModel {
+                    url: Set(entry.url),
+                    old_status: Set(None),
+                    new_status: Set(Some(entry.status)),
+                    user_id: Set(entry.user_id),
+                    guild_id: Set(entry.guild_id),
+                    channel_id: Set(entry.channel_id),
+                    message_id: Set(entry.message_id),
+                    created_at: Set(entry.created_at),
+                    ..Default::default()
+                })
+                .collect();
+            record_wiki_url_events(events, &txn).await?;
+        }
 
         txn.commit().await?;
     }
@@ -288,42 +318,208 @@ async fn migrate(
     Ok(())
 }
 
+/// Builds the embed showing `page_size` results starting at `start`.
+fn search_embed(
+    query: &str,
+    results: &[SearchResult],
+    start: usize,
+    page_size: usize,
+    snapshot: &WikiSnapshot,
+) -> CreateEmbed<'static> {
+    let page = results.get(start..).unwrap_or_default();
+    let page = &page[..page.len().min(page_size)];
+
+    let description = if page.is_empty() {
+        "Nothing found.".to_owned()
+    } else {
+        page.iter()
+            .map(|r| r.line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(format!("Search results for `{query}`"))
+        .description(description);
+
+    let mut footer = format!("Wiki snapshot {}", snapshot.short_hash());
+    if !page.is_empty() && page.len() < results.len() {
+        footer = format!(
+            "Results {}-{} of {} · {footer}",
+            start + 1,
+            start + page.len(),
+            results.len()
+        );
+    }
+    embed = embed.footer(CreateEmbedFooter::new(footer));
+
+    if let Ok(fetched_at) = Timestamp::from_millis(snapshot.fetched_at.timestamp_millis()) {
+        embed = embed.timestamp(fetched_at);
+    }
+
+    embed
+}
+
+/// Builds the paging buttons and section menu, or nothing if everything fits on one page.
+fn search_components(
+    id_prefix: &str,
+    results: &[SearchResult],
+    start: usize,
+    page_size: usize,
+) -> Vec<CreateComponent<'static>> {
+    let mut sections: Vec<(&str, usize, usize)> = Vec::new();
+    for (i, result) in results.iter().enumerate() {
+        match sections.iter_mut().find(|(s, _, _)| *s == result.section) {
+            Some((_, _, count)) => *count += 1,
+            None => sections.push((&result.section, i, 1)),
+        }
+    }
+
+    if results.len() <= page_size && sections.len() <= 1 {
+        return Vec::new();
+    }
+
+    let mut components = vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
+        vec![
+            CreateButton::new(format!("{id_prefix}prev"))
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(start == 0),
+            CreateButton::new(format!("{id_prefix}next"))
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(start + page_size >= results.len()),
+        ]
+        .into(),
+    ))];
+
+    if sections.len() > 1 {
+        let options: Vec<_> = sections
+            .into_iter()
+            .take(25)
+            .map(|(section, first, count)| {
+                let label: String = match section {
+                    "" => "Top of the wiki".to_owned(),
+                    s => s.chars().take(100).collect(),
+                };
+                CreateSelectMenuOption::new(label, first.to_string())
+                    .description(format!("{count} result(s)"))
+            })
+            .collect();
+
+        components.push(CreateComponent::ActionRow(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("{id_prefix}section"),
+                CreateSelectMenuKind::String {
+                    options: options.into(),
+                },
+            )
+            .placeholder("Jump to section"),
+        )));
+    }
+
+    components
+}
+
 /// Search for query in the wiki
 #[poise::command(slash_command)]
 async fn search(
     ctx: Context<'_>,
-    #[description = "The term or phrase you want to search for in the wiki"] query: String,
+    #[description = "Search terms, optionally with section:name, domain:example.com or -exclude"]
+    query: String,
     #[description = "The number of results to skip from the start (default is 0)"]
     #[min = 0]
     offset: Option<u16>,
-    #[description = "The maximum number of search results to return (default is 10)"]
+    #[description = "The number of results to show per page (default is 10)"]
     #[min = 1]
     #[max = 25]
     limit: Option<u8>,
 ) -> Result<(), Error> {
-    let offset = offset.unwrap_or(0) as usize;
-    let limit = limit.unwrap_or(10) as usize;
-    let (entries, matches) = crate::wiki::search_wiki(&query, offset, limit).await?;
+    let mut start = offset.unwrap_or(0) as usize;
+    let page_size = limit.unwrap_or(10) as usize;
+    let snapshot = ctx.data().wiki.get().await?;
+    let results = search_wiki(&snapshot.index, &query);
 
-    let description = if entries.is_empty() {
-        "Nothing found."
-    } else {
-        &entries.join("\n")
-    };
+    let id_prefix = format!("{}:", ctx.id());
+    let components = search_components(&id_prefix, &results, start, page_size);
+    let has_components = !components.is_empty();
 
-    let mut embed = CreateEmbed::new()
-        .title(format!("Search results for `{query}`"))
-        .description(description);
+    let handle = ctx
+        .send(
+            CreateReply::new()
+                .embed(search_embed(&query, &results, start, page_size, &snapshot))
+                .components(components),
+        )
+        .await?;
 
-    if !entries.is_empty() && entries.len() < matches {
-        let start = offset + 1;
-        let end = offset + entries.len();
-        embed = embed.footer(CreateEmbedFooter::new(format!(
-            "Results {start}-{end} of {matches}"
-        )));
+    if !has_components {
+        return Ok(());
     }
 
-    ctx.send(CreateReply::new().embed(embed)).await?;
+    let filter_prefix = id_prefix.clone();
+    while let Some(press) = ComponentInteractionCollector::new(ctx.serenity_context().shard.clone())
+        .filter(move |press| press.data.custom_id.starts_with(filter_prefix.as_str()))
+        .timeout(Duration::from_mins(5))
+        .await
+    {
+        if press.user.id != ctx.author().id {
+            press
+                .create_response(
+                    ctx.http(),
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content("Only the person who ran this search can page through it.")
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            continue;
+        }
+
+        match (
+            &press.data.kind,
+            press
+                .data
+                .custom_id
+                .strip_prefix(id_prefix.as_str())
+                .unwrap_or_default(),
+        ) {
+            (ComponentInteractionDataKind::Button, "prev") => {
+                start = start.saturating_sub(page_size);
+            }
+            (ComponentInteractionDataKind::Button, "next") => {
+                if start + page_size < results.len() {
+                    start += page_size;
+                }
+            }
+            (ComponentInteractionDataKind::StringSelect { values }, "section") => {
+                if let Some(first) = values.first().and_then(|v| v.parse().ok()) {
+                    start = first;
+                }
+            }
+            _ => continue,
+        }
+
+        press
+            .create_response(
+                ctx.http(),
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(search_embed(&query, &results, start, page_size, &snapshot))
+                        .components(search_components(&id_prefix, &results, start, page_size)),
+                ),
+            )
+            .await?;
+    }
+
+    handle
+        .edit(
+            ctx,
+            CreateReply::new()
+                .embed(search_embed(&query, &results, start, page_size, &snapshot))
+                .components(Vec::new()),
+        )
+        .await?;
 
     Ok(())
 }
@@ -360,7 +556,7 @@ async fn context(
     #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
 ) -> Result<(), Error> {
     if let Some(entry) = WikiUrls::find()
-        .filter(wiki_urls::Column::Url.eq(url))
+        .filter(wiki_urls::Column::Url.eq(clean_url(&url)))
         .one(&ctx.data().pool)
         .await?
     {
@@ -368,47 +564,87 @@ async fn context(
             (Some(g), Some(c), Some(m)) => &format!("https://discord.com/channels/{g}/{c}/{m}"),
             _ => "Unavailable",
         };
+        let history = WikiUrlEvents::find()
+            .filter(wiki_url_events::Column::Url.eq(entry.url.as_str()))
+            .order_by_asc(wiki_url_events::Column::CreatedAt)
+            .order_by_asc(wiki_url_events::Column::Id)
+            .all(&ctx.data().pool)
+            .await?
+            .format_timeline(10);
+        let wiki_sections = match ctx.data().wiki.get().await {
+            Ok(snapshot) => {
+                let lines: Vec<_> = snapshot
+                    .index
+                    .entries_for_url(&entry.url)
+                    .take(5)
+                    .map(|e| match e.description.as_str() {
+                        "" => format!("- {}", snapshot.index.format_location(e)),
+                        d => format!("- {} - {d}", snapshot.index.format_location(e)),
+                    })
+                    .collect();
+                if lines.is_empty() {
+                    "Not in the wiki".to_owned()
+                } else {
+                    lines.join("\n")
+                }
+            }
+            Err(_) => "Unavailable".to_owned(),
+        };
+
+        let removal_reason = (entry.status == WikiUrlStatus::Removed)
+            .then(|| format_removal_reason(&entry, 1000))
+            .flatten();
+
+        let mut embed = CreateEmbed::new()
+            .field("URL", entry.url, false)
+            .field(
+                "Updated By",
+                entry
+                    .user_id
+                    .map(|id| format!("<@{id}>"))
+                    .as_deref()
+                    .unwrap_or("Unavailable"),
+                false,
+            )
+            .field("Context", context, false)
+            .field("Wiki Section", wiki_sections, false)
+            .field(
+                "Status",
+                match entry.status {
+                    WikiUrlStatus::Pending => "Pending",
+                    WikiUrlStatus::Added => "Added",
+                    WikiUrlStatus::Removed => "Removed",
+                },
+                false,
+            );
+        if let Some(reason) = removal_reason {
+            embed = embed.field("Removal Reason", reason, false);
+        }
+        embed = embed
+            .field(
+                "Created",
+                format!("<t:{}:R>", entry.created_at.to_utc().timestamp()),
+                true,
+            )
+            .field(
+                "Updated",
+                format!("<t:{}:R>", entry.updated_at.to_utc().timestamp()),
+                true,
+            )
+            .field(
+                "History",
+                history.as_deref().unwrap_or("Unavailable"),
+                false,
+            )
+            .color(match entry.status {
+                WikiUrlStatus::Pending => Color::ORANGE,
+                WikiUrlStatus::Added => Color::DARK_GREEN,
+                WikiUrlStatus::Removed => Color::RED,
+            });
 
         ctx.send(
             CreateReply::new()
-                .embed(
-                    CreateEmbed::new()
-                        .field("URL", entry.url, false)
-                        .field(
-                            "Updated By",
-                            entry
-                                .user_id
-                                .map(|id| format!("<@{id}>"))
-                                .as_deref()
-                                .unwrap_or("Unavailable"),
-                            false,
-                        )
-                        .field("Context", context, false)
-                        .field(
-                            "Status",
-                            match entry.status {
-                                WikiUrlStatus::Pending => "Pending",
-                                WikiUrlStatus::Added => "Added",
-                                WikiUrlStatus::Removed => "Removed",
-                            },
-                            false,
-                        )
-                        .field(
-                            "Created",
-                            format!("<t:{}:R>", entry.created_at.to_utc().timestamp()),
-                            true,
-                        )
-                        .field(
-                            "Updated",
-                            format!("<t:{}:R>", entry.updated_at.to_utc().timestamp()),
-                            true,
-                        )
-                        .color(match entry.status {
-                            WikiUrlStatus::Pending => Color::ORANGE,
-                            WikiUrlStatus::Added => Color::DARK_GREEN,
-                            WikiUrlStatus::Removed => Color::RED,
-                        }),
-                )
+                .embed(embed)
                 .ephemeral(ephemeral.unwrap_or(true)),
         )
         .await?;
@@ -427,75 +663,50 @@ async fn context(
 #[poise::command(prefix_command, owners_only, aliases("incons"))]
 async fn inconsistencies(ctx: Context<'_>) -> Result<(), Error> {
     let entries = WikiUrls::find()
-        .filter(wiki_urls::Column::ChannelId.is_not_in([
-            FmhyChannel::NSFW_ADD_LINKS,
-            FmhyChannel::NSFW_RECENTLY_ADDED,
-            FmhyChannel::NSFW_REMOVED,
-        ]))
         .order_by_desc(wiki_urls::Column::UpdatedAt)
         .all(&ctx.data().pool)
         .await?;
-    let content = reqwest::get(FMHY_SINGLE_PAGE_ENDPOINT)
-        .await?
-        .text()
-        .await?;
-
-    let urls = collect_wiki_urls(&content)
-        .iter()
-        .map(|url| clean_url(url).to_owned())
-        .collect::<Vec<_>>();
-
-    let mut added_not_in_wiki = Vec::new();
-    let mut in_wiki_not_added = Vec::new();
-
-    for entry in entries {
-        let in_wiki = urls.contains(&entry.url);
-
-        match entry.status {
-            WikiUrlStatus::Added => {
-                if !in_wiki {
-                    added_not_in_wiki.push(entry.url);
-                }
-            }
-            WikiUrlStatus::Removed | WikiUrlStatus::Pending => {
-                if in_wiki {
-                    in_wiki_not_added.push(entry.url);
-                }
-            }
-        }
-    }
+    let snapshot = ctx.data().wiki.get().await?;
+    let guild_configs =
+        EntryGuildConfigs::load(ctx.serenity_context().data_ref::<Data>(), &entries).await;
+    let diff = diff_wiki_urls(
+        entries,
+        &snapshot.urls,
+        settled_before(&ctx.data().config().wiki, &snapshot),
+        |entry| guild_configs.is_nsfw(entry),
+    );
 
     let mut embeds = Vec::new();
 
-    for chunk in added_not_in_wiki.chunks_mut(50) {
-        for url in chunk.iter_mut() {
-            url.insert_str(0, "- ");
-        }
-
-        embeds.push(
-            CreateEmbed::new()
-                .title("Added → Removed")
-                .description(chunk.join("\n"))
-                .color(Color::RED),
-        );
-    }
-
-    for chunk in in_wiki_not_added.chunks_mut(50) {
-        for url in chunk.iter_mut() {
-            url.insert_str(0, "- ");
+    for (entries, title, color) in [
+        (&diff.newly_removed, "Added → Removed", Color::RED),
+        (&diff.newly_added, "Removed/Pending → Added", Color::ORANGE),
+    ] {
+        for chunk in entries.chunks(50) {
+            embeds.push(
+                CreateEmbed::new()
+                    .title(title)
+                    .description(
+                        chunk
+                            .iter()
+                            .map(|e| format!("- {}", e.url))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    )
+                    .color(color),
+            );
         }
-
-        embeds.push(
-            CreateEmbed::new()
-                .title("Removed/Pending → Added")
-                .description(chunk.join("\n"))
-                .color(Color::ORANGE),
-        );
     }
 
     if embeds.is_empty() {
-        ctx.say("No inconsistencies found.").await?;
+        ctx.say(format!(
+            "No inconsistencies found in {}.",
+            snapshot.describe()
+        ))
+        .await?;
     } else {
+        ctx.say(format!("Inconsistencies found in {}:", snapshot.describe()))
+            .await?;
         for embed in embeds {
             ctx.channel_id()
                 .send_message(ctx.http(), CreateMessage::new().embed(embed))
@@ -15,12 +15,12 @@ use crate::drama::fill_phrase;
 /// Generate funny piracy community drama
 #[poise::command(slash_command)]
 async fn drama(ctx: Context<'_>) -> Result<(), Error> {
-    let config = &ctx.data().drama_config;
+    let config = ctx.data().config();
 
     let filled = {
         let mut rng = rand::rng();
-        let phrase = config.phrases.choose(&mut rng).unwrap();
-        fill_phrase(phrase, config, &mut rng)
+        let phrase = config.drama.phrases.choose(&mut rng).unwrap();
+        fill_phrase(phrase, &config.drama, &mut rng)
     };
 
     ctx.say(filled).await?;
@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use super::{Command, Context, Error};
+use crate::config::Config;
 
 /// Post the link to the bot's source code
 #[poise::command(
@@ -54,6 +57,28 @@ async fn register(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-pub fn commands() -> [Command; 4] {
-    [source(), shutdown(), uptime(), register()]
+/// Reload the config file without reconnecting
+#[poise::command(prefix_command, owners_only, hide_in_help)]
+async fn reload_config(ctx: Context<'_>) -> Result<(), Error> {
+    let path = Config::path();
+
+    match Config::load(&path) {
+        Ok(config) => {
+            *ctx.data().config.write().unwrap() = Arc::new(config);
+            ctx.say(format!(
+                "Reloaded `{}`. Background task intervals take effect after a restart.",
+                path.display()
+            ))
+            .await?;
+        }
+        Err(e) => {
+            ctx.say(format!("Kept the current config: {e}")).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 5] {
+    [source(), shutdown(), uptime(), register(), reload_config()]
 }
@@ -1,8 +1,12 @@
+pub mod config;
 pub mod fmby;
 pub mod fun;
 pub mod meta;
+pub mod queue;
 pub mod rss;
 pub mod sql;
+pub mod stats;
+pub mod wiki_urls;
 
 use crate::error::Error;
 use crate::types::{Command, Context};
@@ -11,7 +15,11 @@ pub fn commands() -> Vec<Command> {
     meta::commands()
         .into_iter()
         .chain(fmby::commands())
+        .chain(config::commands())
+        .chain(queue::commands())
+        .chain(stats::commands())
         .chain(sql::commands())
+        .chain(wiki_urls::commands())
         .chain(rss::commands())
         .chain(fun::commands())
         .collect()
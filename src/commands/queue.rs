@@ -0,0 +1,212 @@
+use std::fmt::Write;
+
+use poise::CreateReply;
+use poise::serenity_prelude::{Color, CreateEmbed, User};
+use sea_orm::{Condition, QueryOrder, prelude::*};
+
+use super::{Command, Context, Error};
+use crate::db::escape_like;
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::entities::{prelude::*, wiki_urls};
+use crate::formatters::UrlFormatter;
+use crate::guild_config::GuildConfig;
+use crate::url::clean_url;
+
+const ROWS_PER_PAGE: usize = 15;
+
+#[derive(poise::ChoiceParameter)]
+enum QueueOrder {
+    #[name = "Oldest first"]
+    OldestFirst,
+    #[name = "Newest first"]
+    NewestFirst,
+}
+
+/// Where pending entries wait, in the order they are listed.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum QueueGroup {
+    AddLinks(u64),
+    NsfwAddLinks(u64),
+    /// Entries move into a link-testing thread once it is opened for them.
+    LinkTesting,
+    Unknown,
+}
+
+impl QueueGroup {
+    fn of(entry: &wiki_urls::Model, config: &GuildConfig) -> Self {
+        match entry.channel_id.map(|id| id as u64) {
+            Some(id) if config.is(GuildSetting::AddLinks, id) => Self::AddLinks(id),
+            Some(id) if config.is(GuildSetting::NsfwAddLinks, id) => Self::NsfwAddLinks(id),
+            Some(_) => Self::LinkTesting,
+            None => Self::Unknown,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Self::AddLinks(id) | Self::NsfwAddLinks(id) => format!("<#{id}>"),
+            Self::LinkTesting => "Link-testing threads".to_owned(),
+            Self::Unknown => "Unknown channel".to_owned(),
+        }
+    }
+}
+
+/// Groups entries by where they were submitted, keeping the submission channels first.
+fn group_entries(
+    entries: Vec<wiki_urls::Model>,
+    config: &GuildConfig,
+) -> Vec<(QueueGroup, Vec<wiki_urls::Model>)> {
+    let mut groups: Vec<(QueueGroup, Vec<wiki_urls::Model>)> = Vec::new();
+
+    for entry in entries {
+        let key = QueueGroup::of(&entry, config);
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((key, vec![entry])),
+        }
+    }
+
+    groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    groups
+}
+
+/// Lists pending wiki submissions
+#[poise::command(
+    slash_command,
+    install_context = "Guild",
+    interaction_context = "Guild"
+)]
+async fn queue(
+    ctx: Context<'_>,
+    #[description = "Sort order (default is oldest first)"] order: Option<QueueOrder>,
+    #[description = "Only show links submitted by this user"] submitter: Option<User>,
+    #[description = "Only show links on this domain"] domain: Option<String>,
+) -> Result<(), Error> {
+    let order = order.unwrap_or(QueueOrder::OldestFirst);
+    let domain = domain.map(|d| {
+        clean_url(&d)
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_owned()
+    });
+
+    let config = ctx
+        .data()
+        .guild_config
+        .get(ctx.guild_id(), &ctx.data().pool)
+        .await;
+    let submission_channels: Vec<i64> = [GuildSetting::AddLinks, GuildSetting::NsfwAddLinks]
+        .into_iter()
+        .flat_map(|setting| config.ids(setting))
+        .map(|&id| id as i64)
+        .collect();
+
+    let mut query = WikiUrls::find()
+        .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Pending))
+        .filter(
+            Condition::any()
+                .add(wiki_urls::Column::ChannelId.is_in(submission_channels))
+                .add(wiki_urls::Column::GuildId.eq(ctx.guild_id().unwrap().get() as i64)),
+        );
+    if let Some(submitter) = &submitter {
+        query = query.filter(wiki_urls::Column::SubmittedBy.eq(submitter.id.get() as i64));
+    }
+    if let Some(domain) = &domain {
+        let domain = escape_like(domain);
+        query = query.filter(
+            Condition::any()
+                .add(wiki_urls::Column::Url.ilike(format!("{domain}%")))
+                .add(wiki_urls::Column::Url.ilike(format!("%.{domain}%"))),
+        );
+    }
+    query = match order {
+        QueueOrder::OldestFirst => query.order_by_asc(wiki_urls::Column::CreatedAt),
+        QueueOrder::NewestFirst => query.order_by_desc(wiki_urls::Column::CreatedAt),
+    };
+
+    let entries: Vec<_> = query
+        .all(&ctx.data().pool)
+        .await?
+        .into_iter()
+        .filter(|entry| {
+            domain.as_ref().is_none_or(|domain| {
+                let host = entry.url.split('/').next().unwrap_or_default();
+                host == domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+        })
+        .collect();
+
+    if entries.is_empty() {
+        ctx.send(
+            CreateReply::new()
+                .content("No pending submissions match.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let header = format!(
+        "**{} pending submission(s)**, {}\n\n",
+        entries.len(),
+        match order {
+            QueueOrder::OldestFirst => "oldest first",
+            QueueOrder::NewestFirst => "newest first",
+        }
+    );
+
+    let mut pages = Vec::new();
+    let mut page = header.clone();
+    let mut rows = 0;
+
+    for (key, group) in group_entries(entries, &config) {
+        let title = key.title();
+        let mut remaining = group.as_slice();
+
+        while !remaining.is_empty() {
+            if rows == ROWS_PER_PAGE {
+                pages.push(std::mem::replace(&mut page, header.clone()));
+                rows = 0;
+            }
+
+            let (chunk, rest) = remaining.split_at(remaining.len().min(ROWS_PER_PAGE - rows));
+            if let Some(lines) = chunk.format_for_embed(&WikiUrlStatus::Pending) {
+                if rows > 0 {
+                    page.push_str("\n\n");
+                }
+                let _ = write!(page, "{title} ({} pending)\n{lines}", group.len());
+            }
+
+            rows += chunk.len();
+            remaining = rest;
+        }
+    }
+    if rows > 0 {
+        pages.push(page);
+    }
+
+    if let [page] = pages.as_slice() {
+        ctx.send(
+            CreateReply::new().embed(
+                CreateEmbed::new()
+                    .description(page.as_str())
+                    .color(Color::ORANGE),
+            ),
+        )
+        .await?;
+    } else {
+        let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+        poise::builtins::paginate(ctx, &pages).await?;
+    }
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [queue()]
+}
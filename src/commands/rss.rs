@@ -1,15 +1,20 @@
 use itertools::Itertools;
-use poise::CreateReply;
 use poise::serenity_prelude::{
-    AutocompleteChoice, CreateAllowedMentions, CreateAutocompleteResponse,
+    AutocompleteChoice, CreateAllowedMentions, CreateAutocompleteResponse, GuildChannel,
+    Permissions, Role,
 };
-use sea_orm::{ActiveValue::*, QueryFilter, QuerySelect, QueryTrait, prelude::*};
+use poise::{ChoiceParameter, CreateReply};
+use sea_orm::{ActiveValue::*, QueryFilter, QuerySelect, QueryTrait, SqlErr, prelude::*};
 use url::Url;
 
 use super::{Command, Context, Error};
-use crate::entities::enums::RssFeedStatus;
-use crate::entities::{prelude::*, rss_feeds};
-use crate::rss::{RssConfig, RssFetcher};
+use crate::entities::enums::{RssFeedStatus, RssFilterAction, RssFilterKind, RssFilterTarget};
+use crate::entities::{prelude::*, rss_feed_filters, rss_feeds};
+use crate::message::truncate;
+use crate::rss::{FeedEdit, RssConfig, RssFetcher, RssManager, compile_filter_regex};
+
+/// Longest part of a feed's last error shown by `/rss list`.
+const MAX_LISTED_ERROR_LENGTH: usize = 100;
 
 async fn parse_uuid_or_reply(ctx: &Context<'_>, input: &str) -> Option<Uuid> {
     if let Ok(u) = input.parse::<u128>() {
@@ -55,7 +60,7 @@ async fn autocomplete_name<'a>(
     slash_command,
     install_context = "Guild",
     interaction_context = "Guild",
-    subcommands("add", "remove", "rename", "list"),
+    subcommands("add", "remove", "rename", "edit", "pause", "resume", "list", "filter"),
     subcommand_required
 )]
 async fn rss(_ctx: Context<'_>) -> Result<(), Error> {
@@ -78,6 +83,7 @@ async fn add(
             guild_id: Set(ctx.guild_id().unwrap().get() as i64),
             created_by: Set(ctx.author().id.get() as i64),
             status: Set(RssFeedStatus::Active),
+            check_interval_minutes: Set(ctx.data().config().rss.settings.default_check_interval),
             ..Default::default()
         };
 
@@ -154,6 +160,187 @@ async fn rename(
     Ok(())
 }
 
+/// Change the interval, channel, name, ping role or posting mode of an RSS feed (use
+/// autocompletion to select the feed)
+#[poise::command(slash_command)]
+async fn edit(
+    ctx: Context<'_>,
+    #[description = "Name of the RSS feed to edit"]
+    #[autocomplete = "autocomplete_name"]
+    name: String,
+    #[description = "How often to check the feed, in minutes"] interval: Option<i32>,
+    #[description = "The channel to post new entries in"]
+    #[channel_types("Text", "News")]
+    channel: Option<GuildChannel>,
+    #[description = "The new name for the RSS feed"] new_name: Option<String>,
+    #[description = "A role to ping for new entries (narrow it down with mention filters)"]
+    mention_role: Option<Role>,
+    #[description = "Stop pinging a role for new entries"] remove_mention: Option<bool>,
+    #[description = "Post entries through a webhook under the feed's name and icon"]
+    webhook: Option<bool>,
+) -> Result<(), Error> {
+    let Some(uuid) = parse_uuid_or_reply(&ctx, &name).await else {
+        return Ok(());
+    };
+
+    let config = ctx.data().config();
+    let (min, max) = (
+        config.rss.settings.min_check_interval,
+        config.rss.settings.max_check_interval,
+    );
+    if let Some(interval) = interval
+        && !(min..=max).contains(&interval)
+    {
+        ctx.send(
+            CreateReply::new()
+                .content(format!(
+                    "The interval must be between {min} and {max} minutes."
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Pings reach whole roles and webhooks post under the feed's own name and icon, so only
+    // members trusted with mentions may set them up.
+    if mention_role.is_some() || webhook.is_some() {
+        let permissions = ctx
+            .author_member()
+            .await
+            .and_then(|member| member.permissions)
+            .unwrap_or_default();
+        let reason = if !permissions
+            .intersects(Permissions::MANAGE_ROLES | Permissions::MENTION_EVERYONE)
+        {
+            Some(
+                "You need the Manage Roles or Mention Everyone permission to set pings or webhooks.",
+            )
+        } else if mention_role.as_ref().is_some_and(|role| {
+            !role.mentionable() && !permissions.contains(Permissions::MENTION_EVERYONE)
+        }) {
+            Some("You can't ping that role yourself, so the feed can't ping it either.")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            ctx.send(CreateReply::new().content(reason).ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let edit = FeedEdit {
+        name: new_name,
+        channel_id: channel.map(|c| c.id.get()),
+        check_interval_minutes: interval,
+        mention_role_id: match (mention_role, remove_mention) {
+            (_, Some(true)) => Some(None),
+            (Some(role), _) => Some(Some(role.id.get())),
+            _ => None,
+        },
+        use_webhook: webhook,
+    };
+    if edit.is_empty() {
+        ctx.send(
+            CreateReply::new()
+                .content(
+                    "Nothing to change. Pass an interval, a channel, a new name, a role or a mode.",
+                )
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let manager = RssManager::new(ctx.data().pool.clone());
+    let feed = match manager.edit_feed(uuid, edit).await {
+        Ok(feed) => feed,
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            ctx.send(
+                CreateReply::new()
+                    .content("That channel is already subscribed to this feed.")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let content = match feed {
+        Some(feed) => {
+            let mention = feed
+                .mention_role_id
+                .map(|id| format!(", pinging <@&{id}>"))
+                .unwrap_or_default();
+            let webhook = if feed.use_webhook {
+                " through a webhook"
+            } else {
+                ""
+            };
+            format!(
+                "Updated `{}` RSS Feed: checked every {} minute(s) in <#{}>{webhook}{mention}.",
+                feed.name, feed.check_interval_minutes, feed.channel_id
+            )
+        }
+        None => "That RSS feed no longer exists.".to_owned(),
+    };
+    ctx.send(
+        CreateReply::new()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Stop checking an RSS feed until it is resumed (use autocompletion to select the feed)
+#[poise::command(slash_command)]
+async fn pause(
+    ctx: Context<'_>,
+    #[description = "Name of the RSS feed to pause"]
+    #[autocomplete = "autocomplete_name"]
+    name: String,
+) -> Result<(), Error> {
+    set_status(ctx, &name, RssFeedStatus::Inactive).await
+}
+
+/// Resume checking a paused RSS feed (use autocompletion to select the feed)
+#[poise::command(slash_command)]
+async fn resume(
+    ctx: Context<'_>,
+    #[description = "Name of the RSS feed to resume"]
+    #[autocomplete = "autocomplete_name"]
+    name: String,
+) -> Result<(), Error> {
+    set_status(ctx, &name, RssFeedStatus::Active).await
+}
+
+async fn set_status(ctx: Context<'_>, name: &str, status: RssFeedStatus) -> Result<(), Error> {
+    let Some(uuid) = parse_uuid_or_reply(&ctx, name).await else {
+        return Ok(());
+    };
+
+    let feed = RssManager::new(ctx.data().pool.clone())
+        .set_feed_status(uuid, status)
+        .await?;
+
+    let content = match (feed, status) {
+        (Some(feed), RssFeedStatus::Active) => {
+            format!("Resumed `{}` RSS Feed with URL <{}>!", feed.name, feed.url)
+        }
+        (Some(feed), RssFeedStatus::Inactive) => {
+            format!("Paused `{}` RSS Feed with URL <{}>!", feed.name, feed.url)
+        }
+        (None, _) => "That RSS feed no longer exists.".to_owned(),
+    };
+    ctx.reply(content).await?;
+
+    Ok(())
+}
+
 /// Lists the RSS feeds the bot is subscribed to in the current channel
 #[poise::command(slash_command)]
 async fn list(
@@ -172,9 +359,30 @@ async fn list(
         &feeds
             .into_iter()
             .map(|feed| {
+                let paused = match feed.status {
+                    RssFeedStatus::Active => "",
+                    RssFeedStatus::Inactive => " **(paused)**",
+                };
+                let failing = match (feed.consecutive_failures, &feed.last_error) {
+                    (0, _) => String::new(),
+                    (n, Some(error)) => format!(
+                        " ({n} failed check(s) in a row: `{}`)",
+                        truncate(error, MAX_LISTED_ERROR_LENGTH).replace('`', "'")
+                    ),
+                    (n, None) => format!(" ({n} failed check(s) in a row)"),
+                };
+                let last_success = feed
+                    .last_success_at
+                    .map(|at| format!(", last fetched <t:{}:R>", at.timestamp()))
+                    .unwrap_or_else(|| ", never fetched".to_owned());
+                let mention = feed
+                    .mention_role_id
+                    .map(|id| format!(", pings <@&{id}>"))
+                    .unwrap_or_default();
                 format!(
-                    "- {}: <{}> (added by <@{}>)",
-                    feed.name, feed.url, feed.created_by
+                    "- {}: <{}> every {} minute(s) (added by <@{}>){mention}{last_success}\
+                     {paused}{failing}",
+                    feed.name, feed.url, feed.check_interval_minutes, feed.created_by
                 )
             })
             .join("\n")
@@ -191,6 +399,148 @@ async fn list(
     Ok(())
 }
 
+fn describe_filter(filter: &rss_feed_filters::Model) -> String {
+    format!(
+        "`#{}` {} {} {} `{}`",
+        filter.id,
+        filter.action.name().to_lowercase(),
+        filter.target.name().to_lowercase(),
+        filter.kind.name().to_lowercase(),
+        filter.pattern
+    )
+}
+
+#[poise::command(
+    slash_command,
+    subcommands("filter_add", "filter_remove", "filter_list"),
+    subcommand_required
+)]
+async fn filter(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Add an include or exclude filter to an RSS feed (use autocompletion to select the feed)
+#[poise::command(slash_command, rename = "add")]
+async fn filter_add(
+    ctx: Context<'_>,
+    #[description = "Name of the RSS feed to filter"]
+    #[autocomplete = "autocomplete_name"]
+    name: String,
+    #[description = "Whether matching entries are the only ones posted, never posted, or the only ones that ping"]
+    action: RssFilterAction,
+    #[description = "The part of the entry to match"] target: RssFilterTarget,
+    #[description = "Match a keyword (case-insensitive) or a regex"] kind: RssFilterKind,
+    #[description = "The keyword or regex"] pattern: String,
+) -> Result<(), Error> {
+    let Some(uuid) = parse_uuid_or_reply(&ctx, &name).await else {
+        return Ok(());
+    };
+
+    let pattern = pattern.trim().to_owned();
+    let invalid = match kind {
+        _ if pattern.is_empty() => Some("The pattern can't be empty.".to_owned()),
+        RssFilterKind::Regex => compile_filter_regex(&pattern)
+            .err()
+            .map(|e| format!("Invalid regex: ```\n{e}\n```")),
+        RssFilterKind::Keyword => None,
+    };
+    if let Some(content) = invalid {
+        ctx.send(CreateReply::new().content(content).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+
+    let manager = RssManager::new(ctx.data().pool.clone());
+    let Some(feed) = manager.get_feed(uuid).await? else {
+        ctx.send(
+            CreateReply::new()
+                .content("That RSS feed no longer exists.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let filter = manager
+        .add_feed_filter(rss_feed_filters::ActiveModel {
+            feed_id: Set(feed.id),
+            kind: Set(kind),
+            action: Set(action),
+            target: Set(target),
+            pattern: Set(pattern),
+            created_by: Set(ctx.author().id.get() as i64),
+            ..Default::default()
+        })
+        .await?;
+
+    ctx.reply(format!(
+        "Added filter {} to `{}` RSS Feed!",
+        describe_filter(&filter),
+        feed.name
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Remove a filter from an RSS feed (see `/rss filter list` for IDs)
+#[poise::command(slash_command, rename = "remove")]
+async fn filter_remove(
+    ctx: Context<'_>,
+    #[description = "ID of the filter to remove"] id: i32,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    let content = match RssManager::new(ctx.data().pool.clone())
+        .remove_feed_filter(id, guild_id.get())
+        .await?
+    {
+        Some(filter) => format!("Removed filter {}!", describe_filter(&filter)),
+        None => format!("There is no filter `#{id}` in this server."),
+    };
+    ctx.reply(content).await?;
+
+    Ok(())
+}
+
+/// List the filters of an RSS feed (use autocompletion to select the feed)
+#[poise::command(slash_command, rename = "list")]
+async fn filter_list(
+    ctx: Context<'_>,
+    #[description = "Name of the RSS feed"]
+    #[autocomplete = "autocomplete_name"]
+    name: String,
+    #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    let Some(uuid) = parse_uuid_or_reply(&ctx, &name).await else {
+        return Ok(());
+    };
+
+    let filters = RssManager::new(ctx.data().pool.clone())
+        .list_feed_filters(uuid)
+        .await?;
+
+    let content = if filters.is_empty() {
+        "This RSS feed has no filters, so every entry is posted.".to_owned()
+    } else {
+        filters
+            .iter()
+            .map(|f| format!("- {}", describe_filter(f)))
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::new()
+            .content(content)
+            .ephemeral(ephemeral.unwrap_or(true)),
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[poise::command(prefix_command)]
 async fn fetch_feed_title(ctx: Context<'_>, url: String) -> Result<(), Error> {
     let fetcher = RssFetcher::new(&RssConfig::default());
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use poise::CreateReply;
+use poise::serenity_prelude::{Color, CreateEmbed, CreateEmbedFooter, User};
+use sea_orm::sqlx::types::chrono::{NaiveDate, TimeDelta, Utc, Weekday};
+use sea_orm::{QueryOrder, QuerySelect, QueryTrait, prelude::*};
+
+use super::{Command, Context, Error};
+use crate::entities::enums::WikiUrlStatus;
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
+
+const LEADERBOARD_SIZE: usize = 15;
+const RECENT_ADDITIONS: u64 = 5;
+
+/// Link counts per status for one user or week.
+#[derive(Default)]
+struct StatusCounts {
+    pending: i64,
+    added: i64,
+    removed: i64,
+}
+
+impl StatusCounts {
+    fn add(&mut self, status: WikiUrlStatus, count: i64) {
+        match status {
+            WikiUrlStatus::Pending => self.pending += count,
+            WikiUrlStatus::Added => self.added += count,
+            WikiUrlStatus::Removed => self.removed += count,
+        }
+    }
+
+    fn total(&self) -> i64 {
+        self.pending + self.added + self.removed
+    }
+
+    /// Share of reviewed links that were added, if any were reviewed.
+    fn acceptance_rate(&self) -> Option<f64> {
+        let reviewed = self.added + self.removed;
+        (reviewed > 0).then(|| self.added as f64 / reviewed as f64 * 100.0)
+    }
+}
+
+/// Counts the guild's `wiki_urls` rows per submitter and status, optionally only those updated since
+/// `since`.
+async fn count_by_user(
+    ctx: Context<'_>,
+    user_id: Option<i64>,
+    since: Option<DateTimeUtc>,
+) -> Result<HashMap<i64, StatusCounts>, Error> {
+    let rows: Vec<(Option<i64>, WikiUrlStatus, i64)> = WikiUrls::find()
+        .select_only()
+        .column(wiki_urls::Column::SubmittedBy)
+        .column(wiki_urls::Column::Status)
+        .column_as(wiki_urls::Column::Id.count(), "count")
+        .filter(wiki_urls::Column::SubmittedBy.is_not_null())
+        .filter(wiki_urls::Column::GuildId.eq(ctx.guild_id().map(|g| g.get() as i64)))
+        .apply_if(user_id, |query, id| {
+            query.filter(wiki_urls::Column::SubmittedBy.eq(id))
+        })
+        .apply_if(since, |query, since| {
+            query.filter(wiki_urls::Column::UpdatedAt.gte(since))
+        })
+        .group_by(wiki_urls::Column::SubmittedBy)
+        .group_by(wiki_urls::Column::Status)
+        .into_tuple()
+        .all(&ctx.data().pool)
+        .await?;
+
+    let mut counts: HashMap<i64, StatusCounts> = HashMap::new();
+    for (user_id, status, count) in rows {
+        if let Some(user_id) = user_id {
+            counts.entry(user_id).or_default().add(status, count);
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Returns the Monday starting the week `date` falls in.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date.week(Weekday::Mon).first_day()
+}
+
+#[poise::command(
+    slash_command,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    subcommands("user", "leaderboard", "weekly"),
+    subcommand_required
+)]
+async fn stats(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows a member's wiki submissions by status
+#[poise::command(slash_command)]
+async fn user(
+    ctx: Context<'_>,
+    #[description = "The member to show (default is you)"] user: Option<User>,
+    #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    let user = user.as_ref().unwrap_or(ctx.author());
+    let user_id = user.id.get() as i64;
+
+    let counts = count_by_user(ctx, Some(user_id), None)
+        .await?
+        .remove(&user_id)
+        .unwrap_or_default();
+
+    let recent: Vec<String> = WikiUrls::find()
+        .select_only()
+        .column(wiki_urls::Column::Url)
+        .filter(wiki_urls::Column::SubmittedBy.eq(user_id))
+        .filter(wiki_urls::Column::GuildId.eq(ctx.guild_id().map(|g| g.get() as i64)))
+        .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Added))
+        .order_by_desc(wiki_urls::Column::UpdatedAt)
+        .limit(RECENT_ADDITIONS)
+        .into_tuple()
+        .all(&ctx.data().pool)
+        .await?;
+
+    let recent = recent
+        .iter()
+        .map(|url| format!("- {url}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(
+        CreateReply::new()
+            .embed(
+                CreateEmbed::new()
+                    .title(format!("Wiki contributions of {}", user.name))
+                    .field("Total", counts.total().to_string(), true)
+                    .field("Pending", counts.pending.to_string(), true)
+                    .field("Added", counts.added.to_string(), true)
+                    .field("Removed", counts.removed.to_string(), true)
+                    .field(
+                        "Acceptance Rate",
+                        counts
+                            .acceptance_rate()
+                            .map_or_else(|| "Unavailable".to_owned(), |rate| format!("{rate:.1}%")),
+                        true,
+                    )
+                    .field(
+                        "Recently Added",
+                        if recent.is_empty() {
+                            "None"
+                        } else {
+                            recent.as_str()
+                        },
+                        false,
+                    )
+                    .footer(CreateEmbedFooter::new(
+                        "Counts links the member submitted or last moved",
+                    ))
+                    .color(Color::BLURPLE),
+            )
+            .ephemeral(ephemeral.unwrap_or(true)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Shows the top wiki contributors
+#[poise::command(slash_command)]
+async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "Only count links updated in the last this many days (default is 30)"]
+    #[min = 1]
+    #[max = 3650]
+    days: Option<u32>,
+    #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    let days = days.unwrap_or(30);
+    let since = Utc::now() - TimeDelta::days(i64::from(days));
+
+    let mut ranked: Vec<_> = count_by_user(ctx, None, Some(since))
+        .await?
+        .into_iter()
+        .collect();
+    ranked.sort_by(|(a_id, a), (b_id, b)| {
+        b.added
+            .cmp(&a.added)
+            .then(b.total().cmp(&a.total()))
+            .then(a_id.cmp(b_id))
+    });
+
+    let mut description = String::new();
+    for (rank, (user_id, counts)) in ranked.iter().take(LEADERBOARD_SIZE).enumerate() {
+        let _ = writeln!(
+            description,
+            "{}. <@{user_id}> — **{}** added, {} pending, {} removed",
+            rank + 1,
+            counts.added,
+            counts.pending,
+            counts.removed
+        );
+    }
+    if description.is_empty() {
+        description.push_str("No contributions in this window.");
+    }
+
+    ctx.send(
+        CreateReply::new()
+            .embed(
+                CreateEmbed::new()
+                    .title(format!("Top contributors in the last {days} day(s)"))
+                    .description(description)
+                    .footer(CreateEmbedFooter::new(format!(
+                        "{} contributor(s) in total",
+                        ranked.len()
+                    )))
+                    .color(Color::GOLD),
+            )
+            .ephemeral(ephemeral.unwrap_or(true)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Shows how many links were submitted, added and removed each week
+#[poise::command(slash_command)]
+async fn weekly(
+    ctx: Context<'_>,
+    #[description = "Number of weeks to show, including this one (default is 8)"]
+    #[min = 1]
+    #[max = 52]
+    weeks: Option<u32>,
+    #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    let weeks = weeks.unwrap_or(8);
+    let first_week = week_start(Utc::now().date_naive()) - TimeDelta::weeks(i64::from(weeks) - 1);
+    let since = first_week.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+
+    let events: Vec<(
+        DateTimeWithTimeZone,
+        Option<WikiUrlStatus>,
+        Option<WikiUrlStatus>,
+    )> = WikiUrlEvents::find()
+        .select_only()
+        .columns([
+            wiki_url_events::Column::CreatedAt,
+            wiki_url_events::Column::OldStatus,
+            wiki_url_events::Column::NewStatus,
+        ])
+        .filter(wiki_url_events::Column::GuildId.eq(ctx.guild_id().map(|g| g.get() as i64)))
+        .apply_if(since, |query, since| {
+            query.filter(wiki_url_events::Column::CreatedAt.gte(since))
+        })
+        .into_tuple()
+        .all(&ctx.data().pool)
+        .await?;
+
+    // Only count links entering Pending when they start being tracked, i.e. new submissions.
+    let mut by_week: BTreeMap<NaiveDate, StatusCounts> = (0..weeks)
+        .map(|i| {
+            (
+                first_week + TimeDelta::weeks(i64::from(i)),
+                StatusCounts::default(),
+            )
+        })
+        .collect();
+    for (created_at, old_status, new_status) in events {
+        let Some(new_status) = new_status else {
+            continue;
+        };
+        if new_status == WikiUrlStatus::Pending && old_status.is_some() {
+            continue;
+        }
+        if let Some(counts) = by_week.get_mut(&week_start(created_at.to_utc().date_naive())) {
+            counts.add(new_status, 1);
+        }
+    }
+
+    let mut total = StatusCounts::default();
+    let mut description = String::new();
+    for (week, counts) in by_week.iter().rev() {
+        let _ = writeln!(
+            description,
+            "`{week}` — {} submitted, **{}** added, {} removed",
+            counts.pending, counts.added, counts.removed
+        );
+        total.pending += counts.pending;
+        total.added += counts.added;
+        total.removed += counts.removed;
+    }
+
+    ctx.send(
+        CreateReply::new()
+            .embed(
+                CreateEmbed::new()
+                    .title(format!("Link throughput over {weeks} week(s)"))
+                    .description(description)
+                    .field("Submitted", total.pending.to_string(), true)
+                    .field("Added", total.added.to_string(), true)
+                    .field("Removed", total.removed.to_string(), true)
+                    .footer(CreateEmbedFooter::new("Weeks start on Monday (UTC)"))
+                    .color(Color::BLURPLE),
+            )
+            .ephemeral(ephemeral.unwrap_or(true)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [stats()]
+}
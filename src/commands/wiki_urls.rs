@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use poise::CreateReply;
+use poise::serenity_prelude::{Attachment, CreateAttachment};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::sqlx::types::chrono::{NaiveDate, TimeDelta, Utc};
+use sea_orm::{ActiveValue::*, QueryOrder, TransactionTrait, prelude::*};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{Command, Context, Error};
+use crate::db::{ChunkSize, record_wiki_url_events};
+use crate::entities::enums::{RemovalCategory, WikiUrlStatus};
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
+use crate::url::clean_url;
+
+#[derive(poise::ChoiceParameter)]
+enum FileFormat {
+    #[name = "CSV"]
+    Csv,
+    #[name = "JSON"]
+    Json,
+}
+
+#[derive(poise::ChoiceParameter)]
+enum StatusChoice {
+    Pending,
+    Added,
+    Removed,
+}
+
+impl From<StatusChoice> for WikiUrlStatus {
+    fn from(status: StatusChoice) -> Self {
+        match status {
+            StatusChoice::Pending => WikiUrlStatus::Pending,
+            StatusChoice::Added => WikiUrlStatus::Added,
+            StatusChoice::Removed => WikiUrlStatus::Removed,
+        }
+    }
+}
+
+/// A `wiki_urls` row as it appears in export files. Missing IDs on import keep the stored ones.
+#[derive(Serialize, Deserialize)]
+struct WikiUrlRecord {
+    url: String,
+    status: WikiUrlStatus,
+    #[serde(default)]
+    channel_id: Option<i64>,
+    #[serde(default)]
+    user_id: Option<i64>,
+    #[serde(default)]
+    submitted_by: Option<i64>,
+    #[serde(default)]
+    message_id: Option<i64>,
+    #[serde(default)]
+    guild_id: Option<i64>,
+    #[serde(default)]
+    created_at: Option<DateTimeWithTimeZone>,
+    #[serde(default)]
+    updated_at: Option<DateTimeWithTimeZone>,
+    #[serde(default)]
+    removal_reason: Option<String>,
+    #[serde(default)]
+    removal_category: Option<RemovalCategory>,
+}
+
+impl From<wiki_urls::Model> for WikiUrlRecord {
+    fn from(entry: wiki_urls::Model) -> Self {
+        Self {
+            url: entry.url,
+            status: entry.status,
+            channel_id: entry.channel_id,
+            user_id: entry.user_id,
+            submitted_by: entry.submitted_by,
+            message_id: entry.message_id,
+            guild_id: entry.guild_id,
+            created_at: Some(entry.created_at),
+            updated_at: Some(entry.updated_at),
+            removal_reason: entry.removal_reason,
+            removal_category: entry.removal_category,
+        }
+    }
+}
+
+fn status_label(status: WikiUrlStatus) -> &'static str {
+    match status {
+        WikiUrlStatus::Pending => "pending",
+        WikiUrlStatus::Added => "added",
+        WikiUrlStatus::Removed => "removed",
+    }
+}
+
+/// Describes the removal reason of `entry` for the import diff, if it has one.
+fn describe_removal(entry: &wiki_urls::Model) -> String {
+    let parts: Vec<&str> = [
+        entry.removal_category.map(|category| category.label()),
+        entry.removal_reason.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", parts.join(": "))
+    }
+}
+
+/// Canonicalizes `url`, rejecting anything that isn't an HTTP(S) link to a real host.
+fn canonicalize(url: &str) -> Option<String> {
+    let url = url.trim();
+    let candidate = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{url}"))
+    };
+
+    candidate
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .filter(|u| u.host_str().is_some_and(|host| host.contains('.')))
+        .map(|_| clean_url(url))
+}
+
+/// Parses a `YYYY-MM-DD` date as midnight UTC.
+fn parse_date(date: &str) -> Option<DateTimeUtc> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|dt| dt.and_utc())
+}
+
+/// Parses an import file, returning the records along with a description of each invalid row.
+fn parse_records(filename: &str, bytes: &[u8]) -> (Vec<WikiUrlRecord>, Vec<String>) {
+    let mut records = Vec::new();
+    let mut invalid = Vec::new();
+
+    if filename.to_lowercase().ends_with(".json") {
+        match serde_json::from_slice::<Vec<serde_json::Value>>(bytes) {
+            Ok(values) => {
+                for (i, value) in values.into_iter().enumerate() {
+                    match serde_json::from_value(value) {
+                        Ok(record) => records.push(record),
+                        Err(e) => invalid.push(format!("item {}: {e}", i + 1)),
+                    }
+                }
+            }
+            Err(e) => invalid.push(format!("file: {e}")),
+        }
+    } else {
+        let mut reader = csv::Reader::from_reader(bytes);
+        for (i, row) in reader.deserialize().enumerate() {
+            match row {
+                Ok(record) => records.push(record),
+                // Row 1 is the header.
+                Err(e) => invalid.push(format!("row {}: {e}", i + 2)),
+            }
+        }
+    }
+
+    (records, invalid)
+}
+
+#[poise::command(
+    slash_command,
+    owners_only,
+    install_context = "Guild",
+    interaction_context = "Guild",
+    subcommands("export", "import"),
+    subcommand_required
+)]
+async fn wiki_urls(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Exports tracked wiki URLs as a file
+#[poise::command(slash_command, owners_only)]
+async fn export(
+    ctx: Context<'_>,
+    #[description = "File format (default is CSV)"] format: Option<FileFormat>,
+    #[description = "Only export URLs with this status"] status: Option<StatusChoice>,
+    #[description = "Only export URLs submitted in this channel or thread ID"] channel_id: Option<
+        String,
+    >,
+    #[description = "Only export URLs tracked on or after this date (YYYY-MM-DD)"] since: Option<
+        String,
+    >,
+    #[description = "Only export URLs tracked on or before this date (YYYY-MM-DD)"] until: Option<
+        String,
+    >,
+    #[description = "Whether the response should only be visible to you"] ephemeral: Option<bool>,
+) -> Result<(), Error> {
+    let mut query = WikiUrls::find().order_by_asc(wiki_urls::Column::Id);
+
+    if let Some(status) = status {
+        query = query.filter(wiki_urls::Column::Status.eq(WikiUrlStatus::from(status)));
+    }
+    if let Some(channel_id) = channel_id {
+        let Ok(channel_id) = channel_id.trim().parse::<u64>() else {
+            ctx.send(
+                CreateReply::new()
+                    .content("Invalid channel ID.")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        };
+        query = query.filter(wiki_urls::Column::ChannelId.eq(channel_id as i64));
+    }
+    for (date, is_until) in [(since, false), (until, true)] {
+        let Some(date) = date else {
+            continue;
+        };
+        let Some(date) = parse_date(&date) else {
+            ctx.send(
+                CreateReply::new()
+                    .content(format!("Invalid date `{date}`, expected YYYY-MM-DD."))
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        };
+        query = if is_until {
+            query.filter(wiki_urls::Column::CreatedAt.lt(date + TimeDelta::days(1)))
+        } else {
+            query.filter(wiki_urls::Column::CreatedAt.gte(date))
+        };
+    }
+
+    let records: Vec<WikiUrlRecord> = query
+        .all(&ctx.data().pool)
+        .await?
+        .into_iter()
+        .map(WikiUrlRecord::from)
+        .collect();
+
+    let (bytes, extension) = match format.unwrap_or(FileFormat::Csv) {
+        FileFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for record in &records {
+                writer.serialize(record)?;
+            }
+            (writer.into_inner().map_err(|e| e.into_error())?, "csv")
+        }
+        FileFormat::Json => (serde_json::to_vec_pretty(&records)?, "json"),
+    };
+
+    ctx.send(
+        CreateReply::new()
+            .content(format!("Exported {} wiki URL(s).", records.len()))
+            .attachment(CreateAttachment::bytes(
+                bytes,
+                format!("wiki_urls.{extension}"),
+            ))
+            .ephemeral(ephemeral.unwrap_or(true)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Imports wiki URLs from a CSV or JSON file, showing the changes before applying them
+#[poise::command(slash_command, owners_only)]
+async fn import(
+    ctx: Context<'_>,
+    #[description = "A CSV or JSON file in the format produced by export"] file: Attachment,
+    #[description = "Write the changes instead of only showing them (default is false)"]
+    apply: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let apply = apply.unwrap_or(false);
+    let bytes = file.download().await?;
+    let (records, mut invalid) = parse_records(&file.filename, &bytes);
+
+    let mut order = Vec::new();
+    let mut by_url: HashMap<String, WikiUrlRecord> = HashMap::new();
+    for mut record in records {
+        let Some(url) = canonicalize(&record.url) else {
+            invalid.push(format!("invalid URL `{}`", record.url));
+            continue;
+        };
+        record.url = url.clone();
+        if by_url.insert(url.clone(), record).is_none() {
+            order.push(url);
+        }
+    }
+
+    let pool = &ctx.data().pool;
+    let mut existing: HashMap<String, wiki_urls::Model> = HashMap::new();
+    for chunk in order.chunks(WikiUrls::chunk_size()) {
+        for entry in WikiUrls::find()
+            .filter(wiki_urls::Column::Url.is_in(chunk))
+            .all(pool)
+            .await?
+        {
+            existing.insert(entry.url.clone(), entry);
+        }
+    }
+
+    let now: DateTimeWithTimeZone = Utc::now().into();
+    let mut diff = String::new();
+    let mut upserts = Vec::new();
+    let mut events = Vec::new();
+    let (mut added, mut changed, mut unchanged) = (0, 0, 0);
+
+    for url in &order {
+        let record = &by_url[url];
+        let previous = existing.get(url);
+
+        let model = wiki_urls::Model {
+            id: previous.map_or(0, |p| p.id),
+            url: url.clone(),
+            status: record.status,
+            channel_id: record.channel_id.or(previous.and_then(|p| p.channel_id)),
+            user_id: record.user_id.or(previous.and_then(|p| p.user_id)),
+            submitted_by: record
+                .submitted_by
+                .or(previous.and_then(|p| p.submitted_by)),
+            message_id: record.message_id.or(previous.and_then(|p| p.message_id)),
+            guild_id: record.guild_id.or(previous.and_then(|p| p.guild_id)),
+            created_at: previous
+                .map(|p| p.created_at)
+                .or(record.created_at)
+                .unwrap_or(now),
+            updated_at: previous.map_or(now, |p| p.updated_at),
+            removal_reason: (record.status == WikiUrlStatus::Removed)
+                .then(|| {
+                    record
+                        .removal_reason
+                        .clone()
+                        .or(previous.and_then(|p| p.removal_reason.clone()))
+                })
+                .flatten(),
+            removal_category: (record.status == WikiUrlStatus::Removed)
+                .then(|| {
+                    record
+                        .removal_category
+                        .or(previous.and_then(|p| p.removal_category))
+                })
+                .flatten(),
+        };
+
+        match previous {
+            Some(previous) if *previous == model => {
+                unchanged += 1;
+                continue;
+            }
+            Some(previous) => {
+                changed += 1;
+                let removal = describe_removal(&model);
+                if previous.status != model.status {
+                    let _ = writeln!(
+                        diff,
+                        "~ {url}: {} → {}{removal}",
+                        status_label(previous.status),
+                        status_label(model.status)
+                    );
+                } else if previous.removal_reason != model.removal_reason
+                    || previous.removal_category != model.removal_category
+                {
+                    let _ = writeln!(diff, "~ {url}: removal reason updated{removal}");
+                } else {
+                    let _ = writeln!(diff, "~ {url}: IDs updated");
+                }
+            }
+            None => {
+                added += 1;
+                let _ = writeln!(
+                    diff,
+                    "+ {url} ({}){}",
+                    status_label(model.status),
+                    describe_removal(&model)
+                );
+            }
+        }
+
+        if previous.is_none_or(|p| p.status != model.status) {
+            events.push(wiki_url_events::ActiveModel {
+                url: Set(url.clone()),
+                old_status: Set(previous.map(|p| p.status)),
+                new_status: Set(Some(model.status)),
+                user_id: Set(Some(ctx.author().id.get() as i64)),
+                guild_id: Set(model.guild_id),
+                channel_id: Set(model.channel_id),
+                message_id: Set(model.message_id),
+                ..Default::default()
+            });
+        }
+
+        upserts.push(wiki_urls::ActiveModel {
+            url: Set(model.url),
+            status: Set(model.status),
+            channel_id: Set(model.channel_id),
+            user_id: Set(model.user_id),
+            submitted_by: Set(model.submitted_by),
+            message_id: Set(model.message_id),
+            guild_id: Set(model.guild_id),
+            created_at: Set(model.created_at),
+            updated_at: Set(now),
+            removal_reason: Set(model.removal_reason),
+            removal_category: Set(model.removal_category),
+            ..Default::default()
+        });
+    }
+
+    for reason in &invalid {
+        let _ = writeln!(diff, "! {reason}");
+    }
+
+    if apply && !upserts.is_empty() {
+        let txn = pool.begin().await?;
+
+        for chunk in upserts.chunks(WikiUrls::chunk_size()) {
+            WikiUrls::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::column(wiki_urls::Column::Url)
+                        .update_columns([
+                            wiki_urls::Column::Status,
+                            wiki_urls::Column::ChannelId,
+                            wiki_urls::Column::UserId,
+                            wiki_urls::Column::SubmittedBy,
+                            wiki_urls::Column::MessageId,
+                            wiki_urls::Column::GuildId,
+                            wiki_urls::Column::UpdatedAt,
+                            wiki_urls::Column::RemovalReason,
+                            wiki_urls::Column::RemovalCategory,
+                        ])
+                        .to_owned(),
+                )
+                .exec(&txn)
+                .await?;
+        }
+        for chunk in events.chunks(WikiUrlEvents::chunk_size()) {
+            record_wiki_url_events(chunk.to_vec(), &txn).await?;
+        }
+
+        txn.commit().await?;
+    }
+
+    let summary = format!(
+        "{added} new, {changed} changed, {unchanged} unchanged, {} invalid",
+        invalid.len()
+    );
+    let content = if apply {
+        format!("Imported `{}`: {summary}.", file.filename)
+    } else {
+        format!(
+            "Dry run of `{}`: {summary}. Run again with `apply` set to apply these changes.",
+            file.filename
+        )
+    };
+
+    let mut reply = CreateReply::new().content(content).ephemeral(true);
+    if !diff.is_empty() {
+        reply = reply.attachment(CreateAttachment::bytes(
+            diff.into_bytes(),
+            "wiki_urls_diff.txt",
+        ));
+    }
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [wiki_urls()]
+}
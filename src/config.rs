@@ -0,0 +1,260 @@
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::blocklist::BlocklistConfig;
+use crate::drama::DramaConfig;
+use crate::rss::RssConfig;
+use crate::wiki::WikiConfig;
+
+/// Prefix of environment variables overriding config keys, e.g. `FMBY__RSS__EMBED__COLOR`.
+const ENV_PREFIX: &str = "FMBY__";
+const DEFAULT_PATH: &str = "config.toml";
+/// Environment variables read before the config file existed, and the keys they now set.
+const LEGACY_ENV_VARS: &[(&str, &str)] = &[("WIKI_LOG_CHANNEL_ID", "WIKI__LOG_CHANNEL_ID")];
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Failed to parse {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid override {key}: {reason}")]
+    Env { key: String, reason: String },
+    #[error("Invalid config: {0}")]
+    Deserialize(#[from] toml::de::Error),
+    #[error("Invalid value for `{key}`: {reason}")]
+    Invalid {
+        key: &'static str,
+        reason: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rss: RssConfig,
+    pub drama: DramaConfig,
+    pub wiki: WikiConfig,
+    pub blocklist: BlocklistConfig,
+}
+
+impl Config {
+    /// Path of the config file, taken from `CONFIG_PATH` if set.
+    pub fn path() -> PathBuf {
+        env::var_os("CONFIG_PATH").map_or_else(|| PathBuf::from(DEFAULT_PATH), PathBuf::from)
+    }
+
+    /// Loads the defaults, then the file at `path` if it exists, then legacy and `FMBY__`
+    /// environment variables, and validates the result.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut table = match fs::read_to_string(path) {
+            Ok(content) => content
+                .parse::<toml::Table>()
+                .map_err(|source| ConfigError::Parse {
+                    path: path.to_owned(),
+                    source,
+                })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+            Err(source) => {
+                return Err(ConfigError::Read {
+                    path: path.to_owned(),
+                    source,
+                });
+            }
+        };
+
+        for (legacy, key) in LEGACY_ENV_VARS {
+            if let Ok(value) = env::var(legacy) {
+                warn!("{legacy} is deprecated, use {ENV_PREFIX}{key} or the config file instead");
+                apply_override(&mut table, key, &value)?;
+            }
+        }
+
+        for (key, value) in env::vars() {
+            if let Some(key) = key.strip_prefix(ENV_PREFIX) {
+                apply_override(&mut table, key, &value)?;
+            }
+        }
+
+        let config: Self = table.try_into()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let checks = [
+            (
+                self.rss.settings.default_check_interval < 1,
+                "rss.settings.default_check_interval",
+                "must be at least 1",
+            ),
+            (
+                self.rss.settings.min_check_interval < 1,
+                "rss.settings.min_check_interval",
+                "must be at least 1",
+            ),
+            (
+                self.rss.settings.max_check_interval < self.rss.settings.min_check_interval,
+                "rss.settings.max_check_interval",
+                "must be at least min_check_interval",
+            ),
+            (
+                !(self.rss.settings.min_check_interval..=self.rss.settings.max_check_interval)
+                    .contains(&self.rss.settings.default_check_interval),
+                "rss.settings.default_check_interval",
+                "must be between min_check_interval and max_check_interval",
+            ),
+            (
+                self.rss.settings.failure_threshold < 1,
+                "rss.settings.failure_threshold",
+                "must be at least 1",
+            ),
+            (
+                self.rss.settings.max_backoff_minutes < 1,
+                "rss.settings.max_backoff_minutes",
+                "must be at least 1",
+            ),
+            (
+                self.rss.settings.max_concurrent_checks == 0,
+                "rss.settings.max_concurrent_checks",
+                "must be at least 1",
+            ),
+            (
+                self.rss.fetcher.http_timeout_seconds == 0,
+                "rss.fetcher.http_timeout_seconds",
+                "must be at least 1",
+            ),
+            (
+                self.rss.embed.color > 0xFFFFFF,
+                "rss.embed.color",
+                "must be an RGB color",
+            ),
+            (
+                self.drama.phrases.is_empty(),
+                "drama.phrases",
+                "must not be empty",
+            ),
+            (
+                self.wiki.snapshot_refresh_minutes == 0,
+                "wiki.snapshot_refresh_minutes",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.reconcile_interval_minutes == 0,
+                "wiki.reconcile_interval_minutes",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.max_reconcile_changes == 0,
+                "wiki.max_reconcile_changes",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.link_check.interval_minutes == 0,
+                "wiki.link_check.interval_minutes",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.link_check.max_concurrent_hosts == 0,
+                "wiki.link_check.max_concurrent_hosts",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.link_check.http_timeout_seconds == 0,
+                "wiki.link_check.http_timeout_seconds",
+                "must be at least 1",
+            ),
+            (
+                self.wiki.link_check.failure_threshold < 1,
+                "wiki.link_check.failure_threshold",
+                "must be at least 1",
+            ),
+            (
+                self.blocklist.reload_seconds == 0,
+                "blocklist.reload_seconds",
+                "must be at least 1",
+            ),
+        ];
+
+        match checks.into_iter().find(|(failed, _, _)| *failed) {
+            Some((_, key, reason)) => Err(ConfigError::Invalid { key, reason }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Sets the key at `path` (e.g. `RSS__EMBED__COLOR`) to `value`, which is parsed as a TOML
+/// value and otherwise taken as a string.
+fn apply_override(table: &mut toml::Table, path: &str, value: &str) -> Result<(), ConfigError> {
+    let keys: Vec<String> = path.split("__").map(str::to_lowercase).collect();
+    let Some((last, parents)) = keys.split_last() else {
+        return Ok(());
+    };
+
+    let mut current = table;
+    for key in parents {
+        let entry = current
+            .entry(key.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        let toml::Value::Table(next) = entry else {
+            return Err(ConfigError::Env {
+                key: format!("{ENV_PREFIX}{path}"),
+                reason: format!("`{key}` is not a table"),
+            });
+        };
+        current = next;
+    }
+
+    let value = value
+        .parse::<toml::Value>()
+        .unwrap_or_else(|_| toml::Value::String(value.to_owned()));
+    current.insert(last.clone(), value);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_nested_keys() {
+        let mut table: toml::Table = "[rss.embed]\ncolor = 1".parse().unwrap();
+        apply_override(&mut table, "RSS__EMBED__COLOR", "255").unwrap();
+        apply_override(&mut table, "WIKI__LOG_CHANNEL_ID", "1234").unwrap();
+        apply_override(&mut table, "DRAMA__PHRASES", r#"["a", "b"]"#).unwrap();
+
+        let config: Config = table.try_into().unwrap();
+        assert_eq!(config.rss.embed.color, 255);
+        assert_eq!(config.wiki.log_channel_id, Some(1234));
+        assert_eq!(config.drama.phrases, ["a", "b"]);
+        assert_eq!(
+            config.rss.settings.max_entries_per_check,
+            RssConfig::default().settings.max_entries_per_check
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let table: toml::Table = "[wiki.link_check]\nfailure_threshold = 0".parse().unwrap();
+        let config: Config = table.try_into().unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "wiki.link_check.failure_threshold",
+                ..
+            })
+        ));
+
+        let table: toml::Table = "[rss]\nunknown = 1".parse().unwrap();
+        assert!(table.try_into::<Config>().is_err());
+    }
+}
@@ -1,10 +1,15 @@
-use poise::serenity_prelude::Message;
+use std::collections::HashSet;
+
+use poise::serenity_prelude::{GuildThread, Message, UserId};
+use sea_orm::sea_query::OnConflict;
 use sea_orm::sqlx::types::chrono::Utc;
-use sea_orm::{ActiveValue::*, IntoActiveModel, Iterable, prelude::*};
+use sea_orm::{ActiveValue::*, Condition, IntoActiveModel, Iterable, prelude::*};
 
-use crate::constants::FmhyChannel;
-use crate::entities::enums::WikiUrlStatus;
-use crate::entities::{prelude::*, wiki_urls};
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::entities::{prelude::*, task_runs, wiki_url_events, wiki_urls};
+use crate::guild_config::GuildConfig;
+use crate::message::RemovalReason;
+use crate::url::site_key;
 
 pub trait ChunkSize {
     fn chunk_size() -> usize;
@@ -20,16 +25,28 @@ where
     }
 }
 
-pub fn infer_wiki_url_status(channel_id: u64) -> Option<WikiUrlStatus> {
-    match channel_id {
-        FmhyChannel::ADD_LINKS | FmhyChannel::NSFW_ADD_LINKS => Some(WikiUrlStatus::Pending),
-        FmhyChannel::RECENTLY_ADDED | FmhyChannel::NSFW_RECENTLY_ADDED => {
-            Some(WikiUrlStatus::Added)
-        }
-        FmhyChannel::DEAD_SITES | FmhyChannel::REMOVE_SITES | FmhyChannel::NSFW_REMOVED => {
-            Some(WikiUrlStatus::Removed)
-        }
-        _ => None,
+pub fn infer_wiki_url_status(config: &GuildConfig, channel_id: u64) -> Option<WikiUrlStatus> {
+    if config.is_any(
+        &[GuildSetting::AddLinks, GuildSetting::NsfwAddLinks],
+        channel_id,
+    ) {
+        Some(WikiUrlStatus::Pending)
+    } else if config.is_any(
+        &[GuildSetting::RecentlyAdded, GuildSetting::NsfwRecentlyAdded],
+        channel_id,
+    ) {
+        Some(WikiUrlStatus::Added)
+    } else if config.is_any(
+        &[
+            GuildSetting::DeadSites,
+            GuildSetting::RemoveSites,
+            GuildSetting::NsfwRemoved,
+        ],
+        channel_id,
+    ) {
+        Some(WikiUrlStatus::Removed)
+    } else {
+        None
     }
 }
 
@@ -48,19 +65,282 @@ pub async fn get_wiki_urls_by_urls(
         .ok()
 }
 
+/// Escapes the `LIKE` wildcards in `text` so it only matches literally.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Returns tracked entries that live on the same site as any of `urls`, excluding exact matches.
+pub async fn get_same_site_wiki_urls(
+    urls: &[String],
+    pool: &DatabaseConnection,
+) -> Vec<wiki_urls::Model> {
+    let keys: HashSet<String> = urls.iter().filter_map(|url| site_key(url)).collect();
+    if keys.is_empty() {
+        return Vec::new();
+    }
+
+    let condition = keys.iter().fold(Condition::any(), |condition, key| {
+        let key = escape_like(key);
+        condition
+            .add(wiki_urls::Column::Url.ilike(format!("{key}%")))
+            .add(wiki_urls::Column::Url.ilike(format!("%.{key}%")))
+    });
+
+    WikiUrls::find()
+        .filter(condition)
+        .filter(wiki_urls::Column::Url.is_not_in(urls))
+        .all(pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| site_key(&entry.url).is_some_and(|key| keys.contains(&key)))
+        .collect()
+}
+
+/// Builds a status transition event for `url` attributed to the author of `message`.
+///
+/// A `None` old status means the URL started being tracked, a `None` new status means it
+/// stopped being tracked.
+pub fn wiki_url_event_from_message(
+    url: String,
+    old_status: Option<WikiUrlStatus>,
+    new_status: Option<WikiUrlStatus>,
+    message: &Message,
+) -> wiki_url_events::ActiveModel {
+    wiki_url_events::ActiveModel {
+        url: Set(url),
+        old_status: Set(old_status),
+        new_status: Set(new_status),
+        user_id: Set(Some(message.author.id.get() as i64)),
+        guild_id: Set(message.guild_id.map(|g| g.get() as i64)),
+        channel_id: Set(Some(message.channel_id.get() as i64)),
+        message_id: Set(Some(message.id.get() as i64)),
+        ..Default::default()
+    }
+}
+
+/// Returns when the scheduled job `name` last ran, if ever.
+pub async fn get_task_last_run(
+    name: &str,
+    pool: &DatabaseConnection,
+) -> Result<Option<DateTimeWithTimeZone>, DbErr> {
+    let run = TaskRuns::find()
+        .filter(task_runs::Column::Name.eq(name))
+        .one(pool)
+        .await?;
+
+    Ok(run.map(|run| run.last_run_at))
+}
+
+/// Remembers that the scheduled job `name` ran just now.
+pub async fn record_task_run(name: &str, pool: &DatabaseConnection) -> Result<(), DbErr> {
+    TaskRuns::insert(task_runs::ActiveModel {
+        name: Set(name.to_owned()),
+        last_run_at: Set(Utc::now().into()),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(task_runs::Column::Name)
+            .update_column(task_runs::Column::LastRunAt)
+            .to_owned(),
+    )
+    .exec(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn record_wiki_url_events<C>(
+    events: Vec<wiki_url_events::ActiveModel>,
+    conn: &C,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    WikiUrlEvents::insert_many(events).exec(conn).await?;
+
+    Ok(())
+}
+
+/// Starts tracking `urls` as submitted in `message`, skipping any that are already tracked.
+pub async fn insert_wiki_urls_from_message(
+    urls: &[String],
+    message: &Message,
+    status: WikiUrlStatus,
+    removal: Option<&RemovalReason>,
+    pool: &DatabaseConnection,
+) {
+    if urls.is_empty() {
+        return;
+    }
+
+    let Ok(inserted) = WikiUrls::insert_many(urls.iter().map(|url| wiki_urls::ActiveModel {
+        url: Set(url.clone()),
+        user_id: Set(Some(message.author.id.get() as i64)),
+        submitted_by: Set(Some(message.author.id.get() as i64)),
+        guild_id: Set(message.guild_id.map(|g| g.get() as i64)),
+        channel_id: Set(Some(message.channel_id.get() as i64)),
+        message_id: Set(Some(message.id.get() as i64)),
+        status: Set(status),
+        removal_reason: Set(removal.and_then(|r| r.text.clone())),
+        removal_category: Set(removal.and_then(|r| r.category)),
+        ..Default::default()
+    }))
+    .on_conflict(
+        OnConflict::column(wiki_urls::Column::Url)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_with_returning(pool)
+    .await
+    else {
+        return;
+    };
+
+    let events = inserted
+        .into_iter()
+        .map(|entry| wiki_url_event_from_message(entry.url, None, Some(status), message))
+        .collect();
+    let _ = record_wiki_url_events(events, pool).await;
+}
+
+/// Stops tracking the pending entries submitted in any of `message_ids`. Added and removed
+/// entries are left alone.
+pub async fn delete_pending_wiki_urls(message_ids: &[i64], pool: &DatabaseConnection) {
+    if message_ids.is_empty() {
+        return;
+    }
+
+    let Ok(entries) = WikiUrls::find()
+        .filter(wiki_urls::Column::MessageId.is_in(message_ids.iter().copied()))
+        .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Pending))
+        .all(pool)
+        .await
+    else {
+        return;
+    };
+
+    if entries.is_empty() {
+        return;
+    }
+
+    if WikiUrls::delete_many()
+        .filter(wiki_urls::Column::Id.is_in(entries.iter().map(|e| e.id)))
+        .exec(pool)
+        .await
+        .is_ok()
+    {
+        let events = entries
+            .into_iter()
+            .map(|entry| wiki_url_events::ActiveModel {
+                url: Set(entry.url),
+                old_status: Set(Some(entry.status)),
+                new_status: Set(None),
+                guild_id: Set(entry.guild_id),
+                channel_id: Set(entry.channel_id),
+                message_id: Set(entry.message_id),
+                ..Default::default()
+            })
+            .collect();
+        let _ = record_wiki_url_events(events, pool).await;
+    }
+}
+
+/// Moves `entries` to `status`, attributing the change to `message`. The removal reason is
+/// replaced by `removal`, so moving entries out of Removed clears it.
 pub async fn update_wiki_urls_with_message(
     entries: Vec<wiki_urls::Model>,
     message: &Message,
     status: WikiUrlStatus,
+    removal: Option<&RemovalReason>,
     pool: &DatabaseConnection,
 ) {
-    for mut entry in entries.into_iter().map(IntoActiveModel::into_active_model) {
+    let mut events = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let event = wiki_url_event_from_message(
+            entry.url.clone(),
+            Some(entry.status),
+            Some(status),
+            message,
+        );
+
+        let mut entry = entry.into_active_model();
         entry.user_id = Set(Some(message.author.id.get() as i64));
         entry.message_id = Set(Some(message.id.get() as i64));
         entry.channel_id = Set(Some(message.channel_id.get() as i64));
         entry.updated_at = Set(Utc::now().into());
         entry.status = Set(status);
+        entry.removal_reason = Set(removal.and_then(|r| r.text.clone()));
+        entry.removal_category = Set(removal.and_then(|r| r.category));
 
-        let _ = entry.update(pool).await;
+        if entry.update(pool).await.is_ok() {
+            events.push(event);
+        }
     }
+
+    let _ = record_wiki_url_events(events, pool).await;
+}
+
+/// Moves the entries submitted in a link-testing `thread` to `status`, attributing the event to
+/// the `moderator` who tagged the thread when known. The entries keep their submitter.
+///
+/// Reopening a rejected thread only brings back entries that were removed, never added ones.
+pub async fn update_wiki_urls_from_thread(
+    thread: &GuildThread,
+    status: WikiUrlStatus,
+    moderator: Option<UserId>,
+    pool: &DatabaseConnection,
+) {
+    let Ok(entries) = WikiUrls::find()
+        .filter(wiki_urls::Column::ChannelId.eq(thread.id.get() as i64))
+        .filter(match status {
+            WikiUrlStatus::Pending => wiki_urls::Column::Status.eq(WikiUrlStatus::Removed),
+            _ => wiki_urls::Column::Status.ne(status),
+        })
+        .all(pool)
+        .await
+    else {
+        return;
+    };
+
+    let moderator = moderator.map(|id| id.get() as i64);
+    let mut events = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let event = wiki_url_events::ActiveModel {
+            url: Set(entry.url.clone()),
+            old_status: Set(Some(entry.status)),
+            new_status: Set(Some(status)),
+            user_id: Set(moderator),
+            guild_id: Set(entry.guild_id),
+            channel_id: Set(entry.channel_id),
+            message_id: Set(entry.message_id),
+            ..Default::default()
+        };
+
+        let mut entry = entry.into_active_model();
+        entry.updated_at = Set(Utc::now().into());
+        entry.status = Set(status);
+        entry.removal_reason = Set(None);
+        entry.removal_category = Set(None);
+
+        if entry.update(pool).await.is_ok() {
+            events.push(event);
+        }
+    }
+
+    let _ = record_wiki_url_events(events, pool).await;
 }
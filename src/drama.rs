@@ -5,6 +5,7 @@ use rand::prelude::*;
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct DramaConfig {
     pub people: Vec<String>,
     pub servers: Vec<String>,
@@ -13,11 +14,14 @@ pub struct DramaConfig {
     pub replacers: HashMap<String, String>,
 }
 
-impl DramaConfig {
-    pub fn from_config() -> Self {
+/// Falls back to the bundled `resources/drama.json` for anything the config file doesn't set.
+impl Default for DramaConfig {
+    fn default() -> Self {
         serde_json::from_str(include_str!("../resources/drama.json")).unwrap()
     }
+}
 
+impl DramaConfig {
     fn get(&self, key: &str) -> Option<&Vec<String>> {
         match key {
             "people" => Some(&self.people),
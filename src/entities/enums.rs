@@ -1,4 +1,5 @@
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(
@@ -12,15 +13,123 @@ pub enum RssFeedStatus {
     Inactive,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
 #[sea_orm(
     rs_type = "String",
     db_type = "Enum",
     enum_name = "wiki_url_status",
     rename_all = "snake_case"
 )]
+#[serde(rename_all = "snake_case")]
 pub enum WikiUrlStatus {
     Added,
     Removed,
     Pending,
 }
+
+/// Why a link was removed from the wiki, when the removal message says so.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "removal_category",
+    rename_all = "snake_case"
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalCategory {
+    Dead,
+    Malicious,
+    Paywalled,
+    Duplicate,
+}
+
+/// A logical channel, role or forum tag that can be mapped to IDs per guild.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, poise::ChoiceParameter,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "guild_setting",
+    rename_all = "snake_case"
+)]
+pub enum GuildSetting {
+    #[name = "Add links channel"]
+    AddLinks,
+    #[name = "NSFW add links channel"]
+    NsfwAddLinks,
+    #[name = "Recently added channel"]
+    RecentlyAdded,
+    #[name = "NSFW recently added channel"]
+    NsfwRecentlyAdded,
+    #[name = "Remove sites channel"]
+    RemoveSites,
+    #[name = "Dead sites channel"]
+    DeadSites,
+    #[name = "NSFW removed channel"]
+    NsfwRemoved,
+    #[name = "Link testing forum"]
+    LinkTesting,
+    #[name = "Feedback channel"]
+    Feedback,
+    #[name = "Auto thread channel"]
+    AutoThread,
+    #[name = "Moderator role"]
+    ModeratorRole,
+    #[name = "Link testing added tag"]
+    AddedTag,
+    #[name = "Link testing rejected tag"]
+    RejectedTag,
+}
+
+/// How an RSS filter's pattern is matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, poise::ChoiceParameter)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "rss_filter_kind",
+    rename_all = "snake_case"
+)]
+pub enum RssFilterKind {
+    /// Case-insensitive substring.
+    #[name = "Keyword"]
+    Keyword,
+    #[name = "Regex"]
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, poise::ChoiceParameter)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "rss_filter_action",
+    rename_all = "snake_case"
+)]
+pub enum RssFilterAction {
+    /// Only post entries matching at least one include filter.
+    #[name = "Include"]
+    Include,
+    /// Never post entries matching an exclude filter.
+    #[name = "Exclude"]
+    Exclude,
+    /// Only ping the feed's mention role for entries matching at least one mention filter.
+    #[name = "Mention"]
+    Mention,
+}
+
+/// The part of an RSS entry a filter is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, poise::ChoiceParameter)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "rss_filter_target",
+    rename_all = "snake_case"
+)]
+pub enum RssFilterTarget {
+    #[name = "Title"]
+    Title,
+    #[name = "Description"]
+    Description,
+    #[name = "Link"]
+    Link,
+}
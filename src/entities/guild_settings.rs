@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+
+use super::enums::GuildSetting;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "guild_settings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    #[sea_orm(unique_key = "uq_guild_settings_guild_id_setting_target_id")]
+    pub guild_id: i64,
+    #[sea_orm(unique_key = "uq_guild_settings_guild_id_setting_target_id")]
+    pub setting: GuildSetting,
+    #[sea_orm(unique_key = "uq_guild_settings_guild_id_setting_target_id")]
+    pub target_id: i64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,11 @@
 pub mod prelude;
 
 pub mod enums;
+pub mod guild_settings;
 pub mod rss_feed_entries;
+pub mod rss_feed_filters;
 pub mod rss_feeds;
+pub mod task_runs;
+pub mod wiki_url_checks;
+pub mod wiki_url_events;
 pub mod wiki_urls;
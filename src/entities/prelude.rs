@@ -1,3 +1,8 @@
+pub use super::guild_settings::Entity as GuildSettings;
 pub use super::rss_feed_entries::Entity as RssFeedEntries;
+pub use super::rss_feed_filters::Entity as RssFeedFilters;
 pub use super::rss_feeds::Entity as RssFeeds;
+pub use super::task_runs::Entity as TaskRuns;
+pub use super::wiki_url_checks::Entity as WikiUrlChecks;
+pub use super::wiki_url_events::Entity as WikiUrlEvents;
 pub use super::wiki_urls::Entity as WikiUrls;
@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+
+use super::enums::{RssFilterAction, RssFilterKind, RssFilterTarget};
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "rss_feed_filters")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    pub feed_id: Uuid,
+    pub kind: RssFilterKind,
+    pub action: RssFilterAction,
+    pub target: RssFilterTarget,
+    #[sea_orm(column_type = "Text")]
+    pub pattern: String,
+    pub created_by: i64,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(
+        belongs_to,
+        from = "feed_id",
+        to = "id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    pub feed: BelongsTo<super::rss_feeds::Entity>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -20,8 +20,30 @@ pub struct Model {
     pub last_checked_at: DateTimeWithTimeZone,
     pub check_interval_minutes: i32,
     pub status: RssFeedStatus,
+    /// Failed checks since the last successful one.
+    pub consecutive_failures: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    pub last_success_at: Option<DateTimeWithTimeZone>,
+    /// Validators of the last fetched body, sent back to skip unchanged feeds.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub etag: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_modified: Option<String>,
+    /// Hex-encoded SHA-256 of the last fetched body.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub content_hash: Option<String>,
+    /// Role pinged for new entries.
+    pub mention_role_id: Option<i64>,
+    /// Whether entries are posted through a channel webhook under the feed's name and icon.
+    pub use_webhook: bool,
+    /// Icon or logo of the feed, used as the webhook avatar.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub icon_url: Option<String>,
     #[sea_orm(has_many)]
     pub entries: HasMany<super::rss_feed_entries::Entity>,
+    #[sea_orm(has_many)]
+    pub filters: HasMany<super::rss_feed_filters::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,15 @@
+use sea_orm::entity::prelude::*;
+
+/// Last run of a scheduled job that has to survive restarts.
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "task_runs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    #[sea_orm(column_type = "Text", unique_key = "uq_task_runs_name")]
+    pub name: String,
+    pub last_run_at: DateTimeWithTimeZone,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
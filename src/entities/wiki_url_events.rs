@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+
+use super::enums::WikiUrlStatus;
+
+#[sea_orm::model]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "wiki_url_events")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub url: String,
+    pub old_status: Option<WikiUrlStatus>,
+    pub new_status: Option<WikiUrlStatus>,
+    pub user_id: Option<i64>,
+    pub guild_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub message_id: Option<i64>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
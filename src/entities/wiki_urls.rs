@@ -1,6 +1,6 @@
 use sea_orm::entity::prelude::*;
 
-use super::enums::WikiUrlStatus;
+use super::enums::{RemovalCategory, WikiUrlStatus};
 
 #[sea_orm::model]
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -11,12 +11,19 @@ pub struct Model {
     #[sea_orm(column_type = "Text", unique_key = "uq_wiki_urls_url")]
     pub url: String,
     pub channel_id: Option<i64>,
+    /// Member who last moved the link between statuses.
     pub user_id: Option<i64>,
     pub message_id: Option<i64>,
     pub guild_id: Option<i64>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
     pub status: WikiUrlStatus,
+    /// Text of the message that removed the link, without the links themselves.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub removal_reason: Option<String>,
+    pub removal_category: Option<RemovalCategory>,
+    /// Member who submitted the link, kept when it later changes status.
+    pub submitted_by: Option<i64>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,11 +1,13 @@
 use poise::serenity_prelude::{Context, EventHandler, FullEvent, async_trait};
 
 use crate::background_task::start_background_task;
+use crate::blocklist::BlocklistReloader;
 use crate::channels;
 use crate::error::Error;
 use crate::rss::RssScheduler;
 use crate::stale_remover::StaleRemover;
 use crate::types::Data;
+use crate::wiki::{LinkChecker, WikiReconciler, WikiSnapshotRefresher};
 
 pub struct Handler;
 
@@ -34,11 +36,29 @@ pub async fn event_handler(ctx: &Context, event: &FullEvent) -> Result<(), Error
 
                 start_background_task::<RssScheduler>(ctx).await;
                 start_background_task::<StaleRemover>(ctx).await;
+                start_background_task::<WikiSnapshotRefresher>(ctx).await;
+                start_background_task::<WikiReconciler>(ctx).await;
+                start_background_task::<LinkChecker>(ctx).await;
+                start_background_task::<BlocklistReloader>(ctx).await;
             }
         }
         FullEvent::Message { new_message, .. } => {
             channels::global::on_message(ctx, new_message).await;
         }
+        FullEvent::MessageUpdate { event, .. } => {
+            channels::global::on_message_update(ctx, &event.message).await;
+        }
+        FullEvent::MessageDelete {
+            deleted_message_id, ..
+        } => {
+            channels::global::on_message_delete(ctx, &[*deleted_message_id]).await;
+        }
+        FullEvent::MessageDeleteBulk {
+            multiple_deleted_messages_ids,
+            ..
+        } => {
+            channels::global::on_message_delete(ctx, multiple_deleted_messages_ids).await;
+        }
         FullEvent::ReactionAdd { add_reaction, .. } => {
             channels::global::on_reaction_add(ctx, add_reaction).await;
         }
@@ -0,0 +1,344 @@
+use std::collections::HashSet;
+
+use poise::serenity_prelude::{Attachment, Embed, Http, Message, MessageReferenceKind};
+
+/// Largest `.txt` attachment downloaded for link detection.
+const MAX_ATTACHMENT_BYTES: u32 = 64 * 1024;
+
+/// Text of a rich embed, which bots and webhooks use to relay messages.
+#[derive(Debug, Default, Clone)]
+pub struct EmbedText {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub fields: Vec<(String, String)>,
+    pub footer: Option<String>,
+}
+
+impl EmbedText {
+    /// Converts `embed`, skipping link previews, whose text describes links already in the
+    /// message.
+    fn from_embed(embed: &Embed) -> Option<Self> {
+        if embed.kind.as_deref().is_some_and(|kind| kind != "rich") {
+            return None;
+        }
+
+        Some(Self {
+            title: embed.title.as_deref().map(str::to_owned),
+            description: embed.description.as_deref().map(str::to_owned),
+            fields: embed
+                .fields
+                .iter()
+                .map(|f| (f.name.to_string(), f.value.to_string()))
+                .collect(),
+            footer: embed.footer.as_ref().map(|f| f.text.to_string()),
+        })
+    }
+}
+
+/// Everything in a message that can contain links, gathered up front so extractors don't need
+/// the Discord API.
+#[derive(Debug, Default, Clone)]
+pub struct MessageSource {
+    pub content: String,
+    /// Content of each forwarded message.
+    pub forwards: Vec<String>,
+    pub embeds: Vec<EmbedText>,
+    /// Content of each `.txt` attachment.
+    pub attachments: Vec<String>,
+}
+
+impl MessageSource {
+    /// Gathers the content, forwards, rich embeds and `.txt` attachments of `message`, fetching
+    /// the forwarded message when Discord didn't include a snapshot of it.
+    pub async fn fetch(http: &Http, message: &Message) -> Self {
+        let mut source = Self {
+            content: message.content.to_string(),
+            ..Default::default()
+        };
+        let mut attachments: Vec<&Attachment> = message.attachments.iter().collect();
+
+        for snapshot in message.message_snapshots.iter() {
+            source.forwards.push(snapshot.content.to_string());
+            source
+                .embeds
+                .extend(snapshot.embeds.iter().filter_map(EmbedText::from_embed));
+            attachments.extend(snapshot.attachments.iter());
+        }
+
+        let referenced = match &message.message_reference {
+            Some(msg_ref)
+                if msg_ref.kind == MessageReferenceKind::Forward
+                    && message.message_snapshots.is_empty() =>
+            {
+                match msg_ref.message_id {
+                    Some(message_id) => http.get_message(msg_ref.channel_id, message_id).await.ok(),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(referenced) = &referenced {
+            source.forwards.push(referenced.content.to_string());
+            source
+                .embeds
+                .extend(referenced.embeds.iter().filter_map(EmbedText::from_embed));
+            attachments.extend(referenced.attachments.iter());
+        }
+
+        source
+            .embeds
+            .extend(message.embeds.iter().filter_map(EmbedText::from_embed));
+
+        for attachment in attachments {
+            if !is_text_attachment(attachment) {
+                continue;
+            }
+            if let Ok(bytes) = attachment.download().await {
+                source
+                    .attachments
+                    .push(String::from_utf8_lossy(&bytes).into_owned());
+            }
+        }
+
+        source
+    }
+
+    /// Runs every extractor in [`EXTRACTORS`] order and joins their distinct segments, or returns
+    /// `None` if the message has no text at all.
+    pub fn text(&self) -> Option<String> {
+        let mut seen = HashSet::new();
+        let segments: Vec<String> = EXTRACTORS
+            .iter()
+            .flat_map(|extractor| extractor.extract(self))
+            .map(|segment| segment.trim().to_owned())
+            .filter(|segment| !segment.is_empty() && seen.insert(segment.clone()))
+            .collect();
+
+        (!segments.is_empty()).then(|| segments.join("\n"))
+    }
+}
+
+fn is_text_attachment(attachment: &Attachment) -> bool {
+    attachment.size <= MAX_ATTACHMENT_BYTES
+        && (attachment.filename.to_lowercase().ends_with(".txt")
+            || attachment
+                .content_type
+                .as_deref()
+                .is_some_and(|t| t.starts_with("text/plain")))
+}
+
+/// Pulls text that may contain links out of one part of a message.
+pub trait Extractor: Sync {
+    fn extract(&self, source: &MessageSource) -> Vec<String>;
+}
+
+/// The extractors run on every message, in the order their segments are merged.
+pub static EXTRACTORS: &[&dyn Extractor] = &[
+    &ContentExtractor,
+    &ForwardExtractor,
+    &EmbedExtractor,
+    &CodeBlockExtractor,
+    &AttachmentExtractor,
+];
+
+/// The message content outside of code blocks.
+pub struct ContentExtractor;
+
+impl Extractor for ContentExtractor {
+    fn extract(&self, source: &MessageSource) -> Vec<String> {
+        vec![split_code_blocks(&source.content).0]
+    }
+}
+
+/// The content of forwarded messages outside of code blocks.
+pub struct ForwardExtractor;
+
+impl Extractor for ForwardExtractor {
+    fn extract(&self, source: &MessageSource) -> Vec<String> {
+        source
+            .forwards
+            .iter()
+            .map(|forward| split_code_blocks(forward).0)
+            .collect()
+    }
+}
+
+/// The title, description, fields and footer of rich embeds.
+pub struct EmbedExtractor;
+
+impl Extractor for EmbedExtractor {
+    fn extract(&self, source: &MessageSource) -> Vec<String> {
+        source
+            .embeds
+            .iter()
+            .flat_map(|embed| {
+                embed
+                    .title
+                    .iter()
+                    .chain(&embed.description)
+                    .chain(embed.fields.iter().map(|(_, value)| value))
+                    .chain(&embed.footer)
+                    .cloned()
+            })
+            .collect()
+    }
+}
+
+/// The inside of inline and fenced code blocks in the content and forwarded messages.
+pub struct CodeBlockExtractor;
+
+impl Extractor for CodeBlockExtractor {
+    fn extract(&self, source: &MessageSource) -> Vec<String> {
+        std::iter::once(&source.content)
+            .chain(&source.forwards)
+            .flat_map(|text| split_code_blocks(text).1)
+            .collect()
+    }
+}
+
+/// The content of `.txt` attachments.
+pub struct AttachmentExtractor;
+
+impl Extractor for AttachmentExtractor {
+    fn extract(&self, source: &MessageSource) -> Vec<String> {
+        source.attachments.clone()
+    }
+}
+
+/// Splits `text` into the prose outside code blocks and the bodies of its code blocks, dropping
+/// the language tag of fenced blocks. An unclosed block is treated as prose.
+fn split_code_blocks(text: &str) -> (String, Vec<String>) {
+    let mut prose = String::new();
+    let mut blocks = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('`') {
+        let fence = if rest[start..].starts_with("```") {
+            "```"
+        } else {
+            "`"
+        };
+        let body_start = start + fence.len();
+        let Some(len) = rest[body_start..].find(fence) else {
+            break;
+        };
+
+        prose.push_str(&rest[..start]);
+        prose.push(' ');
+
+        let body = &rest[body_start..body_start + len];
+        let body = match (fence, body.split_once('\n')) {
+            ("```", Some((lang, code)))
+                if lang
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '_' | '#')) =>
+            {
+                code
+            }
+            _ => body,
+        };
+        blocks.push(body.to_owned());
+
+        rest = &rest[body_start + len + fence.len()..];
+    }
+    prose.push_str(rest);
+
+    (prose, blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(content: &str) -> MessageSource {
+        MessageSource {
+            content: content.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn content_extractor_skips_code_blocks() {
+        let segments = ContentExtractor.extract(&source("see https://a.com and `https://b.com`"));
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].contains("https://a.com"));
+        assert!(!segments[0].contains("https://b.com"));
+    }
+
+    #[test]
+    fn forward_extractor_reads_every_forward() {
+        let source = MessageSource {
+            forwards: vec!["https://a.com".to_owned(), "https://b.com".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(
+            ForwardExtractor.extract(&source),
+            ["https://a.com", "https://b.com"]
+        );
+    }
+
+    #[test]
+    fn embed_extractor_reads_descriptions_and_fields() {
+        let source = MessageSource {
+            embeds: vec![EmbedText {
+                title: Some("Submission".to_owned()),
+                description: Some("https://a.com".to_owned()),
+                fields: vec![("Message".to_owned(), "https://b.com".to_owned())],
+                footer: None,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            EmbedExtractor.extract(&source),
+            ["Submission", "https://a.com", "https://b.com"]
+        );
+    }
+
+    #[test]
+    fn code_block_extractor_reads_fenced_and_inline_blocks() {
+        let mut fenced = source("```txt\nhttps://a.com\nhttps://b.com```");
+        fenced.forwards.push("try `https://c.com`".to_owned());
+        assert_eq!(
+            CodeBlockExtractor.extract(&fenced),
+            ["https://a.com\nhttps://b.com", "https://c.com"]
+        );
+
+        assert_eq!(
+            CodeBlockExtractor.extract(&source("```https://a.com\nhttps://b.com```")),
+            ["https://a.com\nhttps://b.com"]
+        );
+        assert!(
+            CodeBlockExtractor
+                .extract(&source("unclosed `https://a.com"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn attachment_extractor_reads_text_attachments() {
+        let source = MessageSource {
+            attachments: vec!["https://a.com\nhttps://b.com".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(
+            AttachmentExtractor.extract(&source),
+            ["https://a.com\nhttps://b.com"]
+        );
+    }
+
+    #[test]
+    fn merges_and_dedupes_segments_in_order() {
+        let mut source = source("https://a.com");
+        source.forwards.push("https://a.com".to_owned());
+        source.embeds.push(EmbedText {
+            description: Some("https://b.com".to_owned()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            source.text().as_deref(),
+            Some("https://a.com\nhttps://b.com")
+        );
+        assert_eq!(MessageSource::default().text(), None);
+    }
+}
@@ -1,31 +1,63 @@
 use std::fmt::Write;
 
-use crate::entities::enums::WikiUrlStatus;
-use crate::entities::wiki_urls;
+use crate::entities::enums::{RemovalCategory, WikiUrlStatus};
+use crate::entities::{wiki_url_events, wiki_urls};
+use crate::message::truncate;
+
+impl RemovalCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dead => "Dead",
+            Self::Malicious => "Malicious",
+            Self::Paywalled => "Paywalled",
+            Self::Duplicate => "Duplicate",
+        }
+    }
+}
+
+/// Formats the category and reason of a removed entry, truncating the reason to `max_chars`.
+pub fn format_removal_reason(entry: &wiki_urls::Model, max_chars: usize) -> Option<String> {
+    let reason = entry
+        .removal_reason
+        .as_deref()
+        .map(|reason| truncate(reason, max_chars));
+    match (entry.removal_category, reason) {
+        (Some(category), Some(reason)) => Some(format!("**{}**: {reason}", category.label())),
+        (Some(category), None) => Some(format!("**{}**", category.label())),
+        (None, Some(reason)) => Some(reason),
+        (None, None) => None,
+    }
+}
 
 pub trait UrlFormatter {
     fn format_for_embed(&self, status: &WikiUrlStatus) -> Option<String>;
+    fn format_with_status(&self, max_lines: usize) -> Option<String>;
 }
 
-impl UrlFormatter for Vec<wiki_urls::Model> {
+impl UrlFormatter for [wiki_urls::Model] {
     fn format_for_embed(&self, status: &WikiUrlStatus) -> Option<String> {
         let mut lines = String::new();
         for entry in self.iter().filter(|e| e.status == *status) {
             match status {
                 WikiUrlStatus::Pending | WikiUrlStatus::Removed => {
-                    let (Some(guild_id), Some(channel_id), Some(message_id)) =
-                        (entry.guild_id, entry.channel_id, entry.message_id)
-                    else {
-                        continue;
-                    };
                     if !lines.is_empty() {
                         lines.push('\n');
                     }
-                    let _ = write!(
-                        lines,
-                        "- {} - https://discord.com/channels/{guild_id}/{channel_id}/{message_id}",
-                        entry.url
-                    );
+                    let _ = write!(lines, "- {}", entry.url);
+                    // Imported entries may not know the message they came from.
+                    if let (Some(guild_id), Some(channel_id), Some(message_id)) =
+                        (entry.guild_id, entry.channel_id, entry.message_id)
+                    {
+                        let _ = write!(
+                            lines,
+                            " - https://discord.com/channels/{guild_id}/{channel_id}/{message_id}"
+                        );
+                    }
+                    if *status == WikiUrlStatus::Removed
+                        && let Some(reason) = format_removal_reason(entry, 100)
+                    {
+                        let _ = write!(lines, "\n  - {reason}");
+                    }
                 }
                 WikiUrlStatus::Added => {
                     if !lines.is_empty() {
@@ -37,4 +69,70 @@ impl UrlFormatter for Vec<wiki_urls::Model> {
         }
         (!lines.is_empty()).then_some(lines)
     }
+
+    fn format_with_status(&self, max_lines: usize) -> Option<String> {
+        let mut lines = String::new();
+        for entry in self.iter().take(max_lines) {
+            if !lines.is_empty() {
+                lines.push('\n');
+            }
+            let status = match entry.status {
+                WikiUrlStatus::Pending => "Pending",
+                WikiUrlStatus::Added => "Added",
+                WikiUrlStatus::Removed => "Removed",
+            };
+            let _ = write!(lines, "- {} ({status})", entry.url);
+        }
+        if self.len() > max_lines {
+            let _ = write!(lines, "\n*…and {} more*", self.len() - max_lines);
+        }
+        (!lines.is_empty()).then_some(lines)
+    }
+}
+
+pub trait TimelineFormatter {
+    fn format_timeline(&self, max_lines: usize) -> Option<String>;
+}
+
+impl TimelineFormatter for [wiki_url_events::Model] {
+    /// Formats events (oldest first) as a timeline, keeping only the `max_lines` most recent.
+    fn format_timeline(&self, max_lines: usize) -> Option<String> {
+        let label = |status: Option<WikiUrlStatus>| match status {
+            Some(WikiUrlStatus::Pending) => "Pending",
+            Some(WikiUrlStatus::Added) => "Added",
+            Some(WikiUrlStatus::Removed) => "Removed",
+            None => "Untracked",
+        };
+
+        let mut lines = String::new();
+        let skipped = self.len().saturating_sub(max_lines);
+        if skipped > 0 {
+            let _ = write!(lines, "*{skipped} earlier event(s) not shown*");
+        }
+
+        for event in self.iter().skip(skipped) {
+            if !lines.is_empty() {
+                lines.push('\n');
+            }
+            let _ = write!(
+                lines,
+                "- <t:{}:d> {} → {}",
+                event.created_at.to_utc().timestamp(),
+                label(event.old_status),
+                label(event.new_status)
+            );
+            if let Some(user_id) = event.user_id {
+                let _ = write!(lines, " by <@{user_id}>");
+            }
+            if let (Some(guild_id), Some(channel_id), Some(message_id)) =
+                (event.guild_id, event.channel_id, event.message_id)
+            {
+                let _ = write!(
+                    lines,
+                    " ([message](https://discord.com/channels/{guild_id}/{channel_id}/{message_id}))"
+                );
+            }
+        }
+        (!lines.is_empty()).then_some(lines)
+    }
 }
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use poise::serenity_prelude::GuildId;
+use sea_orm::prelude::*;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::constants::link_testing::ForumTag;
+use crate::constants::{AUTO_THREAD_CHANNELS, FmhyChannel, FmhyServerRole};
+use crate::entities::enums::GuildSetting;
+use crate::entities::{guild_settings, prelude::*};
+
+/// What a [`GuildSetting`] maps to.
+pub enum SettingKind {
+    Channel,
+    Role,
+    ForumTag,
+}
+
+impl GuildSetting {
+    pub fn kind(self) -> SettingKind {
+        match self {
+            Self::ModeratorRole => SettingKind::Role,
+            Self::AddedTag | Self::RejectedTag => SettingKind::ForumTag,
+            _ => SettingKind::Channel,
+        }
+    }
+
+    /// The IDs used on the FMHY server, which apply wherever the setting isn't configured.
+    pub fn defaults(self) -> &'static [u64] {
+        match self {
+            Self::AddLinks => &[FmhyChannel::ADD_LINKS],
+            Self::NsfwAddLinks => &[FmhyChannel::NSFW_ADD_LINKS],
+            Self::RecentlyAdded => &[FmhyChannel::RECENTLY_ADDED],
+            Self::NsfwRecentlyAdded => &[FmhyChannel::NSFW_RECENTLY_ADDED],
+            Self::RemoveSites => &[FmhyChannel::REMOVE_SITES],
+            Self::DeadSites => &[FmhyChannel::DEAD_SITES],
+            Self::NsfwRemoved => &[FmhyChannel::NSFW_REMOVED],
+            Self::LinkTesting => &[FmhyChannel::LINK_TESTING],
+            Self::Feedback => &[FmhyChannel::FEEDBACK],
+            Self::AutoThread => AUTO_THREAD_CHANNELS,
+            Self::ModeratorRole => &[
+                FmhyServerRole::FIRST_MATE,
+                FmhyServerRole::CELESTIAL,
+                FmhyServerRole::CAPTAIN,
+            ],
+            Self::AddedTag => &[ForumTag::ADDED],
+            Self::RejectedTag => &[ForumTag::REJECTED],
+        }
+    }
+
+    /// Formats `id` as a mention, or as plain code for forum tags.
+    pub fn mention(self, id: u64) -> String {
+        match self.kind() {
+            SettingKind::Channel => format!("<#{id}>"),
+            SettingKind::Role => format!("<@&{id}>"),
+            SettingKind::ForumTag => format!("`{id}`"),
+        }
+    }
+}
+
+/// The channel, role and forum tag IDs configured for one guild.
+#[derive(Default)]
+pub struct GuildConfig {
+    overrides: HashMap<GuildSetting, Vec<u64>>,
+}
+
+impl GuildConfig {
+    /// Returns the IDs mapped to `setting`, falling back to [`GuildSetting::defaults`].
+    pub fn ids(&self, setting: GuildSetting) -> &[u64] {
+        self.overrides
+            .get(&setting)
+            .map_or(setting.defaults(), Vec::as_slice)
+    }
+
+    pub fn is(&self, setting: GuildSetting, id: u64) -> bool {
+        self.ids(setting).contains(&id)
+    }
+
+    pub fn is_any(&self, settings: &[GuildSetting], id: u64) -> bool {
+        settings.iter().any(|&setting| self.is(setting, id))
+    }
+
+    pub fn is_configured(&self, setting: GuildSetting) -> bool {
+        self.overrides.contains_key(&setting)
+    }
+}
+
+/// Caches each guild's [`GuildConfig`], loading it from `guild_settings` on first use.
+pub struct GuildConfigStore {
+    cache: RwLock<HashMap<GuildId, Arc<GuildConfig>>>,
+}
+
+impl GuildConfigStore {
+    pub fn new() -> Self {
+        Self {
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the config of `guild_id`, or the defaults outside of guilds.
+    pub async fn get(
+        &self,
+        guild_id: Option<GuildId>,
+        pool: &DatabaseConnection,
+    ) -> Arc<GuildConfig> {
+        let Some(guild_id) = guild_id else {
+            return Arc::default();
+        };
+
+        if let Some(config) = self.cache.read().await.get(&guild_id) {
+            return config.clone();
+        }
+
+        let rows = match GuildSettings::find()
+            .filter(guild_settings::Column::GuildId.eq(guild_id.get() as i64))
+            .all(pool)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("Failed to load settings of guild {guild_id}: {e}");
+                return Arc::default();
+            }
+        };
+
+        let mut config = GuildConfig::default();
+        for row in rows {
+            config
+                .overrides
+                .entry(row.setting)
+                .or_default()
+                .push(row.target_id as u64);
+        }
+
+        let config = Arc::new(config);
+        self.cache.write().await.insert(guild_id, config.clone());
+
+        config
+    }
+
+    /// Drops the cached config of `guild_id` so the next lookup reloads it.
+    pub async fn invalidate(&self, guild_id: GuildId) {
+        self.cache.write().await.remove(&guild_id);
+    }
+}
@@ -1,24 +1,30 @@
 mod background_task;
+mod blocklist;
 mod channels;
 mod commands;
+mod config;
 mod constants;
 mod db;
 mod drama;
 mod entities;
 mod error;
 mod events;
+mod extractors;
 mod formatters;
+mod guild_config;
 mod message;
 mod migration;
 mod rss;
 mod stale_remover;
+#[cfg(test)]
+mod test_utils;
 mod types;
 mod url;
 mod wiki;
 
 use std::env;
-use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
 use migration::Migrator;
@@ -87,6 +93,9 @@ async fn main() {
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
 
+    let config = config::Config::load(&config::Config::path())
+        .unwrap_or_else(|e| panic!("Failed to load config: {e}"));
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set.");
     let mut conn_opts = ConnectOptions::new(database_url);
     conn_opts
@@ -109,8 +118,10 @@ async fn main() {
             time_started: Instant::now(),
             has_started: AtomicBool::new(false),
             pool,
-            rss_config: rss::RssConfig::default(),
-            drama_config: drama::DramaConfig::from_config(),
+            guild_config: guild_config::GuildConfigStore::new(),
+            config: RwLock::new(Arc::new(config)),
+            wiki: wiki::WikiSnapshotStore::new(),
+            blocklist: blocklist::BlocklistStore::new(),
         }))
         .await
         .expect("failed to create client");
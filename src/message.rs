@@ -1,26 +1,139 @@
-use std::borrow::Cow;
-
-use poise::serenity_prelude::{Http, Message, MessageReferenceKind};
-
-/// Gets message content, falling back to referenced message content if the main content is empty.
-/// Returns `None` if both the message and its referenced message (if any) have empty content.
-pub async fn get_content_or_referenced<'a>(
-    http: &'a Http,
-    message: &'a Message,
-) -> Option<Cow<'a, str>> {
-    if !message.content.is_empty() {
-        return Some(Cow::Borrowed(message.content.as_str()));
+use crate::entities::enums::RemovalCategory;
+use crate::url::strip_urls;
+
+const MAX_REMOVAL_REASON_LENGTH: usize = 500;
+
+/// Keywords hinting at why a link was removed, checked in order so the more specific categories
+/// win over "dead".
+const REMOVAL_KEYWORDS: &[(RemovalCategory, &[&str])] = &[
+    (
+        RemovalCategory::Malicious,
+        &[
+            "malware",
+            "malicious",
+            "virus",
+            "scam",
+            "phishing",
+            "unsafe",
+            "miner",
+            "adware",
+        ],
+    ),
+    (
+        RemovalCategory::Paywalled,
+        &["paywall", "paywalled", "paid", "subscription", "premium"],
+    ),
+    (
+        RemovalCategory::Duplicate,
+        &[
+            "duplicate",
+            "dupe",
+            "already in",
+            "already listed",
+            "mirror of",
+        ],
+    ),
+    (
+        RemovalCategory::Dead,
+        &[
+            "dead",
+            "down",
+            "offline",
+            "404",
+            "not working",
+            "doesn't work",
+            "broken",
+            "shut down",
+            "shutdown",
+        ],
+    ),
+];
+
+/// Why links were removed, as given by the text around them in the removal message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalReason {
+    pub text: Option<String>,
+    pub category: Option<RemovalCategory>,
+}
+
+impl RemovalReason {
+    /// Parses the reason out of `content`, using `fallback` as the category when no keyword
+    /// matches, e.g. for messages in the dead sites channel.
+    pub fn parse(content: &str, fallback: Option<RemovalCategory>) -> Self {
+        let stripped = strip_urls(content).replace("<>", " ");
+        let text = stripped
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_matches(|c: char| c.is_ascii_punctuation() || c == '—' || c.is_whitespace())
+            .to_owned();
+
+        let category = infer_removal_category(&text).or(fallback);
+        let text = (!text.is_empty()).then(|| truncate(&text, MAX_REMOVAL_REASON_LENGTH));
+
+        Self { text, category }
     }
+}
 
-    let msg_ref = message
-        .message_reference
-        .as_ref()
-        .filter(|m| m.kind == MessageReferenceKind::Forward)?;
+fn infer_removal_category(text: &str) -> Option<RemovalCategory> {
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let normalized = format!(" {} ", words.join(" "));
 
-    let referenced = http
-        .get_message(msg_ref.channel_id, msg_ref.message_id?)
-        .await
-        .ok()?;
+    REMOVAL_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| {
+            keywords
+                .iter()
+                .any(|keyword| normalized.contains(&format!(" {keyword} ")))
+        })
+        .map(|(category, _)| *category)
+}
+
+/// Truncates `text` to at most `max` characters, marking the cut with an ellipsis.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_owned();
+    }
 
-    (!referenced.content.is_empty()).then(|| Cow::Owned(referenced.content.into_string()))
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reason_around_links() {
+        let reason = RemovalReason::parse(
+            "https://example.com - contains a crypto miner, see <https://other.org>",
+            None,
+        );
+        assert_eq!(reason.text.as_deref(), Some("contains a crypto miner, see"));
+        assert_eq!(reason.category, Some(RemovalCategory::Malicious));
+    }
+
+    #[test]
+    fn falls_back_when_no_keyword_matches() {
+        let reason = RemovalReason::parse("https://example.com", Some(RemovalCategory::Dead));
+        assert_eq!(reason.text, None);
+        assert_eq!(reason.category, Some(RemovalCategory::Dead));
+
+        let reason = RemovalReason::parse("https://example.com downloads are fine", None);
+        assert_eq!(reason.category, None);
+    }
+
+    #[test]
+    fn prefers_specific_categories() {
+        let reason = RemovalReason::parse("site is down and now paywalled https://a.com", None);
+        assert_eq!(reason.category, Some(RemovalCategory::Paywalled));
+
+        let reason = RemovalReason::parse("dupe of https://a.com, already listed", None);
+        assert_eq!(reason.category, Some(RemovalCategory::Duplicate));
+    }
 }
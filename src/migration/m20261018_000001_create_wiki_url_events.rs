@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::enums::WikiUrlStatusEnum;
+use crate::entities::{prelude::*, wiki_url_events};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_WIKI_URL_EVENTS_URL: &str = "idx_wiki_url_events_url";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WikiUrlEvents)
+                    .if_not_exists()
+                    .col(pk_auto(wiki_url_events::Column::Id))
+                    .col(text(wiki_url_events::Column::Url))
+                    .col(custom_null(
+                        wiki_url_events::Column::OldStatus,
+                        WikiUrlStatusEnum,
+                    ))
+                    .col(custom_null(
+                        wiki_url_events::Column::NewStatus,
+                        WikiUrlStatusEnum,
+                    ))
+                    .col(big_integer_null(wiki_url_events::Column::UserId))
+                    .col(big_integer_null(wiki_url_events::Column::GuildId))
+                    .col(big_integer_null(wiki_url_events::Column::ChannelId))
+                    .col(big_integer_null(wiki_url_events::Column::MessageId))
+                    .col(
+                        timestamp_with_time_zone(wiki_url_events::Column::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(IDX_WIKI_URL_EVENTS_URL)
+                    .table(WikiUrlEvents)
+                    .col(wiki_url_events::Column::Url)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(IDX_WIKI_URL_EVENTS_URL).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(WikiUrlEvents).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, FromQueryResult, TransactionTrait};
+use sea_orm_migration::prelude::*;
+use url::Url;
+
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The columns of `wiki_urls` this migration reads. Later migrations add columns to the entity
+/// that don't exist yet when this one runs, so it can't load full models.
+#[derive(FromQueryResult)]
+struct WikiUrlRow {
+    id: i32,
+    url: String,
+    created_at: DateTimeWithTimeZone,
+}
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    /// Re-canonicalizes every stored URL, merging rows that end up with the same URL into the
+    /// most recently updated one while keeping the earliest creation time.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let txn = manager.get_connection().begin().await?;
+
+        let select = Query::select()
+            .columns([
+                wiki_urls::Column::Id,
+                wiki_urls::Column::Url,
+                wiki_urls::Column::CreatedAt,
+            ])
+            .from(WikiUrls)
+            .order_by(wiki_urls::Column::UpdatedAt, Order::Desc)
+            .to_owned();
+
+        let mut groups: HashMap<String, Vec<WikiUrlRow>> = HashMap::new();
+        for row in txn.query_all(&select).await? {
+            let entry = WikiUrlRow::from_query_result(&row, "")?;
+            groups.entry(clean_url(&entry.url)).or_default().push(entry);
+        }
+
+        for (canonical, mut entries) in groups {
+            if entries.len() == 1 && entries[0].url == canonical {
+                continue;
+            }
+
+            let survivor = entries.remove(0);
+            let created_at = entries
+                .iter()
+                .map(|e| e.created_at)
+                .chain([survivor.created_at])
+                .min()
+                .unwrap_or(survivor.created_at);
+
+            let mut old_urls: Vec<String> = entries.iter().map(|e| e.url.clone()).collect();
+            old_urls.push(survivor.url.clone());
+
+            if !entries.is_empty() {
+                txn.execute(
+                    &Query::delete()
+                        .from_table(WikiUrls)
+                        .and_where(
+                            Expr::col(wiki_urls::Column::Id).is_in(entries.iter().map(|e| e.id)),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+            }
+
+            txn.execute(
+                &Query::update()
+                    .table(WikiUrls)
+                    .value(wiki_urls::Column::Url, canonical.clone())
+                    .value(wiki_urls::Column::CreatedAt, created_at)
+                    .and_where(Expr::col(wiki_urls::Column::Id).eq(survivor.id))
+                    .to_owned(),
+            )
+            .await?;
+
+            txn.execute(
+                &Query::update()
+                    .table(WikiUrlEvents)
+                    .value(wiki_url_events::Column::Url, canonical)
+                    .and_where(Expr::col(wiki_url_events::Column::Url).is_in(old_urls))
+                    .to_owned(),
+            )
+            .await?;
+        }
+
+        txn.commit().await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
+
+// Everything below is a copy of `crate::url::clean_url` as it was when this migration was
+// written, so that later changes to canonicalization don't change what it does to a fresh
+// database.
+
+const TRACKING_PARAMS: &[&str] = &[
+    "__hsfp",
+    "__hssc",
+    "__hstc",
+    "__s",
+    "__twitter_impression",
+    "_ga",
+    "_gat",
+    "_gid",
+    "_gl",
+    "_hsenc",
+    "_hsmi",
+    "_openstat",
+    "_pk_campaign",
+    "_pk_kwd",
+    "_pk_source",
+    "_twitter_sess_id",
+    "aff",
+    "aff_id",
+    "affiliate_id",
+    "campaign",
+    "campaign_id",
+    "ceneo_spo",
+    "cid",
+    "cmpid",
+    "correlation_id",
+    "dclid",
+    "epik",
+    "fb_action_ids",
+    "fb_action_types",
+    "fb_ref",
+    "fb_source",
+    "fbclid",
+    "from",
+    "from_source",
+    "gbraid",
+    "gclid",
+    "gclsrc",
+    "gs_l",
+    "hsCtaTracking",
+    "hsa_acc",
+    "hsa_ad",
+    "hsa_cam",
+    "hsa_grp",
+    "hsa_kw",
+    "hsa_mt",
+    "hsa_net",
+    "hsa_ol",
+    "hsa_src",
+    "hsa_ver",
+    "icid",
+    "igsh",
+    "igshid",
+    "itm_campaign",
+    "itm_content",
+    "itm_medium",
+    "itm_source",
+    "itm_term",
+    "mc_cid",
+    "mc_eid",
+    "mc_tc",
+    "mkt_tok",
+    "ml_subscriber",
+    "ml_subscriber_hash",
+    "msclkid",
+    "msi",
+    "ncid",
+    "oly_anon_id",
+    "oly_enc_id",
+    "os_ehash",
+    "partner_id",
+    "rb_clickid",
+    "ref",
+    "ref_src",
+    "ref_url",
+    "s_cid",
+    "sc_campaign",
+    "sc_channel",
+    "sc_content",
+    "sc_country",
+    "sc_geo",
+    "sc_medium",
+    "sc_outcome",
+    "share_id",
+    "source",
+    "sourceid",
+    "spm",
+    "spm_id",
+    "srsltid",
+    "tracking_source",
+    "trk",
+    "trkCampaign",
+    "ttclid",
+    "twclid",
+    "utm_campaign",
+    "utm_content",
+    "utm_id",
+    "utm_medium",
+    "utm_reader",
+    "utm_source",
+    "utm_term",
+    "vero_conv",
+    "vero_id",
+    "wbraid",
+    "wickedid",
+    "wtmc",
+    "wtrid",
+    "wtzmc",
+    "yclid",
+    "zanpid",
+];
+
+fn is_tracking_param(key: &str) -> bool {
+    TRACKING_PARAMS.binary_search(&key).is_ok()
+}
+
+fn strip_tracking_params(url: &mut Url) {
+    if url.query().is_none() {
+        return;
+    }
+
+    let needs_cleaning = url.query_pairs().any(|(k, _)| is_tracking_param(&k));
+    if !needs_cleaning {
+        return;
+    }
+
+    let original_len = url.query().map_or(0, str::len);
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| !is_tracking_param(k))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+
+    if kept.is_empty() {
+        url.set_query(None);
+        return;
+    }
+
+    let mut serializer = url::form_urlencoded::Serializer::new(String::with_capacity(original_len));
+    for (k, v) in &kept {
+        serializer.append_pair(k, v);
+    }
+    url.set_query(Some(&serializer.finish()));
+}
+
+/// Path suffixes that point at a directory's default document.
+const INDEX_DOCUMENTS: &[&str] = &["index.htm", "index.html", "index.php"];
+
+/// Branch names GitHub and GitLab treat as the default view of a repository.
+const DEFAULT_BRANCHES: &[&str] = &["main", "master"];
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// Decodes percent-encoded unreserved characters and uppercases the remaining escapes.
+fn normalize_percent_encoding(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) if is_unreserved(byte) => out.push(byte as char),
+            Some(byte) => out.push_str(&format!("%{byte:02X}")),
+            None => {
+                let ch = input[i..].chars().next().unwrap_or_default();
+                out.push(ch);
+                i += ch.len_utf8();
+                continue;
+            }
+        }
+        i += 3;
+    }
+
+    out
+}
+
+/// Collapses repository URLs on GitHub and GitLab that all point at the same repository.
+fn normalize_forge_path(host: &str, path: &str) -> String {
+    let mut segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    let (is_github, is_gitlab) = (host == "github.com", host == "gitlab.com");
+    if !is_github && !is_gitlab {
+        return path.to_owned();
+    }
+
+    // GitLab routes repository views through a `/-/` segment, GitHub does not.
+    let view = if is_gitlab {
+        segments.iter().position(|s| s == "-")
+    } else {
+        (segments.len() > 2).then_some(2)
+    };
+
+    if let Some(view) = view {
+        let rest = &segments[view + usize::from(is_gitlab)..];
+        let is_default_view = match rest {
+            [kind, branch] => kind == "tree" && DEFAULT_BRANCHES.contains(&branch.as_str()),
+            [kind, branch, file] => {
+                kind == "blob"
+                    && DEFAULT_BRANCHES.contains(&branch.as_str())
+                    && file.eq_ignore_ascii_case("readme.md")
+            }
+            _ => false,
+        };
+        if is_default_view {
+            segments.truncate(view);
+        }
+    }
+
+    let repo_end = view.unwrap_or(segments.len());
+    if let Some(repo) = segments[..repo_end].last_mut()
+        && let Some(stripped) = repo.strip_suffix(".git")
+    {
+        *repo = stripped.to_owned();
+    }
+
+    if is_github {
+        for segment in segments.iter_mut().take(2) {
+            *segment = segment.to_lowercase();
+        }
+    }
+
+    let mut normalized = String::with_capacity(path.len());
+    for segment in &segments {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    normalized
+}
+
+/// Canonicalizes a URL into the scheme-less form stored in `wiki_urls`.
+fn clean_url(url: &str) -> String {
+    let url = url.trim();
+    let parsed = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{url}"))
+    };
+
+    let Ok(mut parsed) = parsed.map_err(|_| ()).and_then(|u| {
+        (matches!(u.scheme(), "http" | "https") && u.host_str().is_some())
+            .then_some(u)
+            .ok_or(())
+    }) else {
+        return url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_start_matches("www.")
+            .trim_end_matches('/')
+            .to_owned();
+    };
+
+    strip_tracking_params(&mut parsed);
+
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| {
+            let rest = host.strip_prefix("ww")?;
+            let digits = rest.find('.')?;
+            rest[..digits]
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| &rest[digits + 1..])
+        })
+        .unwrap_or(host)
+        .trim_end_matches('.')
+        .to_owned();
+
+    let mut canonical = host.clone();
+    if let Some(port) = parsed.port().filter(|p| !matches!(p, 80 | 443)) {
+        canonical.push_str(&format!(":{port}"));
+    }
+
+    let mut path = normalize_forge_path(&host, &normalize_percent_encoding(parsed.path()));
+    for index in INDEX_DOCUMENTS {
+        if let Some(stripped) = path.strip_suffix(index)
+            && stripped.ends_with('/')
+        {
+            path.truncate(stripped.len());
+            break;
+        }
+    }
+    canonical.push_str(path.trim_end_matches('/'));
+
+    let mut pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(k, v)| !(k == "tab" && v.ends_with("-ov-file")))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if !pairs.is_empty() {
+        pairs.sort();
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+        for (k, v) in &pairs {
+            serializer.append_pair(k, v);
+        }
+        canonical.push('?');
+        canonical.push_str(&serializer.finish());
+    }
+
+    // Hash-based routes are part of the page address, plain anchors are not.
+    if let Some(fragment) = parsed.fragment().filter(|f| f.starts_with(['/', '!'])) {
+        canonical.push('#');
+        canonical.push_str(fragment);
+    }
+
+    canonical
+}
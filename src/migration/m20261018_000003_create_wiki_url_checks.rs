@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, wiki_url_checks};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const UQ_WIKI_URL_CHECKS_URL: &str = "uq_wiki_url_checks_url";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WikiUrlChecks)
+                    .if_not_exists()
+                    .col(pk_auto(wiki_url_checks::Column::Id))
+                    .col(text(wiki_url_checks::Column::Url))
+                    .col(integer(wiki_url_checks::Column::ConsecutiveFailures).default(0))
+                    .col(integer_null(wiki_url_checks::Column::StatusCode))
+                    .col(text_null(wiki_url_checks::Column::RedirectUrl))
+                    .col(text_null(wiki_url_checks::Column::LastError))
+                    .col(
+                        timestamp_with_time_zone(wiki_url_checks::Column::LastCheckedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(
+                        wiki_url_checks::Column::LastSuccessAt,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UQ_WIKI_URL_CHECKS_URL)
+                    .table(WikiUrlChecks)
+                    .col(wiki_url_checks::Column::Url)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(UQ_WIKI_URL_CHECKS_URL).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(WikiUrlChecks).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
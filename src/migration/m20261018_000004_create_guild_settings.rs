@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::enums::{GuildSetting, GuildSettingEnum};
+use crate::entities::{guild_settings, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const UQ_GUILD_SETTINGS_GUILD_ID_SETTING_TARGET_ID: &str =
+    "uq_guild_settings_guild_id_setting_target_id";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(GuildSettingEnum)
+                    .values(GuildSetting::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(GuildSettings)
+                    .if_not_exists()
+                    .col(pk_auto(guild_settings::Column::Id))
+                    .col(big_integer(guild_settings::Column::GuildId))
+                    .col(custom(guild_settings::Column::Setting, GuildSettingEnum))
+                    .col(big_integer(guild_settings::Column::TargetId))
+                    .col(
+                        timestamp_with_time_zone(guild_settings::Column::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UQ_GUILD_SETTINGS_GUILD_ID_SETTING_TARGET_ID)
+                    .table(GuildSettings)
+                    .col(guild_settings::Column::GuildId)
+                    .col(guild_settings::Column::Setting)
+                    .col(guild_settings::Column::TargetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(UQ_GUILD_SETTINGS_GUILD_ID_SETTING_TARGET_ID)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(GuildSettings).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(GuildSettingEnum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
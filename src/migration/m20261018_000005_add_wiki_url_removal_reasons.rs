@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::enums::{RemovalCategory, RemovalCategoryEnum};
+use crate::entities::{prelude::*, wiki_urls};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RemovalCategoryEnum)
+                    .values(RemovalCategory::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WikiUrls)
+                    .add_column(text_null(wiki_urls::Column::RemovalReason))
+                    .add_column(custom_null(
+                        wiki_urls::Column::RemovalCategory,
+                        RemovalCategoryEnum,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WikiUrls)
+                    .drop_column(wiki_urls::Column::RemovalReason)
+                    .drop_column(wiki_urls::Column::RemovalCategory)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RemovalCategoryEnum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
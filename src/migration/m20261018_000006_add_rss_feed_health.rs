@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, rss_feeds};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .add_column(integer(rss_feeds::Column::ConsecutiveFailures).default(0))
+                    .add_column(text_null(rss_feeds::Column::LastError))
+                    .add_column(timestamp_with_time_zone_null(
+                        rss_feeds::Column::LastSuccessAt,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .drop_column(rss_feeds::Column::ConsecutiveFailures)
+                    .drop_column(rss_feeds::Column::LastError)
+                    .drop_column(rss_feeds::Column::LastSuccessAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, rss_feeds};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .add_column(text_null(rss_feeds::Column::Etag))
+                    .add_column(text_null(rss_feeds::Column::LastModified))
+                    .add_column(text_null(rss_feeds::Column::ContentHash))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .drop_column(rss_feeds::Column::Etag)
+                    .drop_column(rss_feeds::Column::LastModified)
+                    .drop_column(rss_feeds::Column::ContentHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::enums::{
+    RssFilterActionEnum, RssFilterKind, RssFilterKindEnum, RssFilterTarget, RssFilterTargetEnum,
+};
+use crate::entities::{prelude::*, rss_feed_filters, rss_feeds};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const FK_RSS_FEED_FILTERS_FEED_ID: &str = "fk_rss_feed_filters_feed_id";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RssFilterKindEnum)
+                    .values(RssFilterKind::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RssFilterActionEnum)
+                    // Listed by hand, later migrations add values to the enum.
+                    .values([Alias::new("include"), Alias::new("exclude")])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RssFilterTargetEnum)
+                    .values(RssFilterTarget::iden_values())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RssFeedFilters)
+                    .if_not_exists()
+                    .col(pk_auto(rss_feed_filters::Column::Id))
+                    .col(uuid(rss_feed_filters::Column::FeedId))
+                    .col(custom(rss_feed_filters::Column::Kind, RssFilterKindEnum))
+                    .col(custom(
+                        rss_feed_filters::Column::Action,
+                        RssFilterActionEnum,
+                    ))
+                    .col(custom(
+                        rss_feed_filters::Column::Target,
+                        RssFilterTargetEnum,
+                    ))
+                    .col(text(rss_feed_filters::Column::Pattern))
+                    .col(big_integer(rss_feed_filters::Column::CreatedBy))
+                    .col(
+                        timestamp_with_time_zone(rss_feed_filters::Column::CreatedAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name(FK_RSS_FEED_FILTERS_FEED_ID)
+                    .from(RssFeedFilters, rss_feed_filters::Column::FeedId)
+                    .to(RssFeeds, rss_feeds::Column::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RssFeedFilters).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RssFilterKindEnum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RssFilterActionEnum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RssFilterTargetEnum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
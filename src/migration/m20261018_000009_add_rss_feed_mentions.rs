@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, rss_feeds};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .add_column(big_integer_null(rss_feeds::Column::MentionRoleId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Databases set up while the previous migration created the type from the current enum
+        // already have the value.
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TYPE rss_filter_action ADD VALUE IF NOT EXISTS 'mention'")
+            .await?;
+
+        Ok(())
+    }
+
+    /// Postgres can't drop enum values, so `mention` filters are deleted and the value is kept.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM rss_feed_filters WHERE action = 'mention'")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .drop_column(rss_feeds::Column::MentionRoleId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, rss_feeds};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .add_column(boolean(rss_feeds::Column::UseWebhook).default(false))
+                    .add_column(text_null(rss_feeds::Column::IconUrl))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RssFeeds)
+                    .drop_column(rss_feeds::Column::UseWebhook)
+                    .drop_column(rss_feeds::Column::IconUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, task_runs};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const UQ_TASK_RUNS_NAME: &str = "uq_task_runs_name";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskRuns)
+                    .if_not_exists()
+                    .col(pk_auto(task_runs::Column::Id))
+                    .col(text(task_runs::Column::Name))
+                    .col(timestamp_with_time_zone(task_runs::Column::LastRunAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UQ_TASK_RUNS_NAME)
+                    .table(TaskRuns)
+                    .col(task_runs::Column::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(UQ_TASK_RUNS_NAME).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TaskRuns).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::{prelude::*, wiki_urls};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    /// Adds the submitter of each link, taken from the event that started tracking it or, for
+    /// links tracked before events were recorded, from whoever last updated it.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WikiUrls)
+                    .add_column(big_integer_null(wiki_urls::Column::SubmittedBy))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE wiki_urls SET submitted_by = COALESCE(
+                    (
+                        SELECT e.user_id FROM wiki_url_events e
+                        WHERE e.url = wiki_urls.url
+                            AND e.old_status IS NULL
+                            AND e.new_status IS NOT NULL
+                        ORDER BY e.id
+                        LIMIT 1
+                    ),
+                    user_id
+                )",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WikiUrls)
+                    .drop_column(wiki_urls::Column::SubmittedBy)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
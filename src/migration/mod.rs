@@ -1,4 +1,16 @@
 mod m20220101_000001_create_table;
+mod m20261018_000001_create_wiki_url_events;
+mod m20261018_000002_canonicalize_wiki_urls;
+mod m20261018_000003_create_wiki_url_checks;
+mod m20261018_000004_create_guild_settings;
+mod m20261018_000005_add_wiki_url_removal_reasons;
+mod m20261018_000006_add_rss_feed_health;
+mod m20261018_000007_add_rss_feed_validators;
+mod m20261018_000008_create_rss_feed_filters;
+mod m20261018_000009_add_rss_feed_mentions;
+mod m20261018_000010_add_rss_feed_webhooks;
+mod m20261018_000011_create_task_runs;
+mod m20261018_000012_add_wiki_url_submitters;
 
 use async_trait::async_trait;
 use sea_orm_migration::prelude::*;
@@ -8,6 +20,20 @@ pub struct Migrator;
 #[async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20261018_000001_create_wiki_url_events::Migration),
+            Box::new(m20261018_000002_canonicalize_wiki_urls::Migration),
+            Box::new(m20261018_000003_create_wiki_url_checks::Migration),
+            Box::new(m20261018_000004_create_guild_settings::Migration),
+            Box::new(m20261018_000005_add_wiki_url_removal_reasons::Migration),
+            Box::new(m20261018_000006_add_rss_feed_health::Migration),
+            Box::new(m20261018_000007_add_rss_feed_validators::Migration),
+            Box::new(m20261018_000008_create_rss_feed_filters::Migration),
+            Box::new(m20261018_000009_add_rss_feed_mentions::Migration),
+            Box::new(m20261018_000010_add_rss_feed_webhooks::Migration),
+            Box::new(m20261018_000011_create_task_runs::Migration),
+            Box::new(m20261018_000012_add_wiki_url_submitters::Migration),
+        ]
     }
 }
@@ -3,15 +3,40 @@ use std::time::Duration;
 
 use itertools::Itertools;
 use regex::Regex;
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use sea_orm::sqlx::types::chrono::Utc;
 use sea_orm::{ActiveValue::*, prelude::*};
+use sha2::{Digest, Sha256};
 
 use crate::entities::{rss_feed_entries, rss_feeds};
 use crate::error::Error;
 use crate::rss::RssConfig;
 
+/// What a feed responded with, as stored to make the next request conditional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_hash: String,
+}
+
+pub enum FeedFetch {
+    /// The server answered 304, or sent the same body as last time. In the latter case the
+    /// response's validators are kept, so the next request can be answered with a 304.
+    NotModified(Option<FeedValidators>),
+    Modified {
+        entries: Vec<rss_feed_entries::ActiveModel>,
+        validators: FeedValidators,
+        /// The feed's icon, or its logo if it has none.
+        icon_url: Option<String>,
+    },
+}
+
 pub struct RssFetcher {
     client: reqwest::Client,
+    /// Whether unchanged feeds are skipped, which `debug_force_post` turns off.
+    conditional: bool,
 }
 
 impl RssFetcher {
@@ -22,17 +47,61 @@ impl RssFetcher {
             .build()
             .expect("HTTP client creation failed");
 
-        Self { client }
+        Self {
+            client,
+            conditional: !config.settings.debug_force_post,
+        }
     }
 
-    pub async fn fetch_feed(
-        &self,
-        feed: &rss_feeds::Model,
-    ) -> Result<Vec<rss_feed_entries::ActiveModel>, Error> {
-        let url = &feed.url;
-        let response = self.client.get(url).send().await?.error_for_status()?;
-        let content = response.text().await?;
-        let parsed_feed = feed_rs::parser::parse(content.as_bytes()).map_err(Error::FeedParse)?;
+    /// Fetches the feed, sending back the validators stored from the last fetch so unchanged
+    /// feeds are neither downloaded nor parsed again.
+    pub async fn fetch_feed(&self, feed: &rss_feeds::Model) -> Result<FeedFetch, Error> {
+        let mut request = self.client.get(&feed.url);
+        if self.conditional {
+            if let Some(etag) = &feed.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &feed.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(FeedFetch::NotModified(None));
+        }
+
+        let response = response.error_for_status()?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+
+        let content = response.bytes().await?;
+        let content_hash = format!("{:x}", Sha256::digest(&content));
+        if self.conditional && feed.content_hash.as_deref() == Some(content_hash.as_str()) {
+            let validators =
+                (feed.etag != etag || feed.last_modified != last_modified).then(|| {
+                    FeedValidators {
+                        etag,
+                        last_modified,
+                        content_hash,
+                    }
+                });
+            return Ok(FeedFetch::NotModified(validators));
+        }
+
+        let parsed_feed = feed_rs::parser::parse(content.as_ref()).map_err(Error::FeedParse)?;
+
+        let icon_url = parsed_feed
+            .icon
+            .or(parsed_feed.logo)
+            .map(|image| image.uri)
+            .filter(|uri| uri.starts_with("http://") || uri.starts_with("https://"));
 
         let mut entries: Vec<_> = parsed_feed
             .entries
@@ -49,7 +118,15 @@ impl RssFetcher {
             },
         );
 
-        Ok(entries)
+        Ok(FeedFetch::Modified {
+            entries,
+            validators: FeedValidators {
+                etag,
+                last_modified,
+                content_hash,
+            },
+            icon_url,
+        })
     }
 
     fn convert_to_active_model(
@@ -215,3 +292,149 @@ fn find_first_image(html: &str) -> Option<String> {
             url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//")
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::entities::enums::RssFeedStatus;
+    use crate::test_utils;
+
+    const FEED: &str = "<?xml version=\"1.0\"?><rss version=\"2.0\"><channel><title>Stub</title>\
+                        <item><guid>1</guid><title>First</title></item></channel></rss>";
+    const ETAG_HEADER: &str = "\"v2\"";
+    const LAST_MODIFIED_HEADER: &str = "Sun, 18 Oct 2026 00:00:00 GMT";
+
+    /// Serves [`FEED`] with fresh validators, or a 304 on `/not-modified`, and records the
+    /// requests it receives.
+    async fn stub_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+
+        let base = test_utils::stub_server(move |request| {
+            let request = request.to_lowercase();
+            let response = match test_utils::request_path(&request) {
+                Some("/not-modified") => {
+                    "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_owned()
+                }
+                _ => format!(
+                    "HTTP/1.1 200 OK\r\nETag: {ETAG_HEADER}\r\n\
+                     Last-Modified: {LAST_MODIFIED_HEADER}\r\nContent-Length: {}\r\n\
+                     \r\n{FEED}",
+                    FEED.len()
+                ),
+            };
+            received.lock().unwrap().push(request);
+
+            async move { response }
+        })
+        .await;
+
+        (base, requests)
+    }
+
+    fn fetcher() -> RssFetcher {
+        test_utils::install_crypto_provider();
+        RssFetcher::new(&RssConfig::default())
+    }
+
+    fn feed(url: String) -> rss_feeds::Model {
+        rss_feeds::Model {
+            id: Uuid::nil(),
+            url,
+            name: "Stub".to_owned(),
+            channel_id: 0,
+            guild_id: 0,
+            created_by: 0,
+            created_at: Default::default(),
+            last_checked_at: Default::default(),
+            check_interval_minutes: 5,
+            status: RssFeedStatus::Active,
+            consecutive_failures: 0,
+            last_error: None,
+            last_success_at: None,
+            etag: None,
+            last_modified: None,
+            content_hash: None,
+            mention_role_id: None,
+            use_webhook: false,
+            icon_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_stored_validators_and_stops_on_304() {
+        let (base, requests) = stub_server().await;
+        let feed = rss_feeds::Model {
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: Some(LAST_MODIFIED_HEADER.to_owned()),
+            ..feed(format!("{base}/not-modified"))
+        };
+
+        let fetched = fetcher().fetch_feed(&feed).await.unwrap();
+        assert!(matches!(fetched, FeedFetch::NotModified(None)));
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.contains("if-none-match: \"v1\""));
+        assert!(request.contains(&format!(
+            "if-modified-since: {}",
+            LAST_MODIFIED_HEADER.to_lowercase()
+        )));
+    }
+
+    #[tokio::test]
+    async fn parses_changed_bodies() {
+        let (base, requests) = stub_server().await;
+
+        let FeedFetch::Modified {
+            entries,
+            validators,
+            ..
+        } = fetcher().fetch_feed(&feed(base)).await.unwrap()
+        else {
+            panic!("expected the feed to be parsed");
+        };
+        assert_eq!(entries.len(), 1);
+        assert_eq!(validators.etag.as_deref(), Some(ETAG_HEADER));
+        assert_eq!(
+            validators.last_modified.as_deref(),
+            Some(LAST_MODIFIED_HEADER)
+        );
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(!request.contains("if-none-match"));
+        assert!(!request.contains("if-modified-since"));
+    }
+
+    #[tokio::test]
+    async fn skips_identical_bodies_and_keeps_new_validators() {
+        let (base, _) = stub_server().await;
+        let content_hash = format!("{:x}", Sha256::digest(FEED.as_bytes()));
+        let feed = rss_feeds::Model {
+            content_hash: Some(content_hash.clone()),
+            ..feed(base)
+        };
+
+        let fetched = fetcher().fetch_feed(&feed).await.unwrap();
+        let FeedFetch::NotModified(Some(validators)) = fetched else {
+            panic!("expected the identical body to be skipped with its validators");
+        };
+        assert_eq!(
+            validators,
+            FeedValidators {
+                etag: Some(ETAG_HEADER.to_owned()),
+                last_modified: Some(LAST_MODIFIED_HEADER.to_owned()),
+                content_hash,
+            }
+        );
+
+        let feed = rss_feeds::Model {
+            etag: validators.etag,
+            last_modified: validators.last_modified,
+            ..feed
+        };
+        let fetched = fetcher().fetch_feed(&feed).await.unwrap();
+        assert!(matches!(fetched, FeedFetch::NotModified(None)));
+    }
+}
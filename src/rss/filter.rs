@@ -0,0 +1,205 @@
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+
+use crate::entities::enums::{RssFilterAction, RssFilterKind, RssFilterTarget};
+use crate::entities::{rss_feed_entries, rss_feed_filters};
+
+enum Matcher {
+    /// Lowercased keyword.
+    Keyword(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(kind: RssFilterKind, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(match kind {
+            RssFilterKind::Keyword => Self::Keyword(pattern.to_lowercase()),
+            RssFilterKind::Regex => Self::Regex(compile_filter_regex(pattern)?),
+        })
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Keyword(keyword) => text.to_lowercase().contains(keyword),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Compiles the pattern of a regex filter, as done when it is evaluated.
+pub fn compile_filter_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(1 << 20)
+        .build()
+}
+
+struct FeedFilter {
+    action: RssFilterAction,
+    target: RssFilterTarget,
+    matcher: Matcher,
+}
+
+impl FeedFilter {
+    fn matches(&self, entry: &rss_feed_entries::Model) -> bool {
+        let text = match self.target {
+            RssFilterTarget::Title => Some(entry.title.as_str()),
+            RssFilterTarget::Description => entry.description.as_deref(),
+            RssFilterTarget::Link => entry.link.as_deref(),
+        };
+        text.is_some_and(|text| self.matcher.is_match(text))
+    }
+}
+
+/// The compiled filters of one feed.
+pub struct FeedFilters {
+    filters: Vec<FeedFilter>,
+}
+
+impl FeedFilters {
+    /// Compiles `filters`, skipping regexes that no longer compile.
+    pub fn new(filters: Vec<rss_feed_filters::Model>) -> Self {
+        let filters = filters
+            .into_iter()
+            .filter_map(|filter| match Matcher::new(filter.kind, &filter.pattern) {
+                Ok(matcher) => Some(FeedFilter {
+                    action: filter.action,
+                    target: filter.target,
+                    matcher,
+                }),
+                Err(e) => {
+                    warn!("Skipping invalid RSS filter {}: {e}", filter.id);
+                    None
+                }
+            })
+            .collect();
+
+        Self { filters }
+    }
+
+    /// Whether `entry` should be posted: it must match no exclude filter, and at least one
+    /// include filter if the feed has any.
+    pub fn allows(&self, entry: &rss_feed_entries::Model) -> bool {
+        let mut includes = self
+            .filters
+            .iter()
+            .filter(|f| f.action == RssFilterAction::Include)
+            .peekable();
+        let included = includes.peek().is_none() || includes.any(|f| f.matches(entry));
+
+        included
+            && !self
+                .filters
+                .iter()
+                .filter(|f| f.action == RssFilterAction::Exclude)
+                .any(|f| f.matches(entry))
+    }
+
+    /// Whether posting `entry` should ping the feed's mention role: it must match at least one
+    /// mention filter if the feed has any.
+    pub fn mentions(&self, entry: &rss_feed_entries::Model) -> bool {
+        let mut mentions = self
+            .filters
+            .iter()
+            .filter(|f| f.action == RssFilterAction::Mention)
+            .peekable();
+        mentions.peek().is_none() || mentions.any(|f| f.matches(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::prelude::Uuid;
+
+    use super::*;
+
+    fn filter(
+        kind: RssFilterKind,
+        action: RssFilterAction,
+        target: RssFilterTarget,
+        pattern: &str,
+    ) -> rss_feed_filters::Model {
+        rss_feed_filters::Model {
+            id: 0,
+            feed_id: Uuid::nil(),
+            kind,
+            action,
+            target,
+            pattern: pattern.to_owned(),
+            created_by: 0,
+            created_at: Default::default(),
+        }
+    }
+
+    fn entry(title: &str, link: Option<&str>) -> rss_feed_entries::Model {
+        rss_feed_entries::Model {
+            id: Uuid::nil(),
+            feed_id: Uuid::nil(),
+            entry_id: title.to_owned(),
+            title: title.to_owned(),
+            link: link.map(ToOwned::to_owned),
+            description: None,
+            thumbnail_url: None,
+            published_at: None,
+            created_at: Default::default(),
+            message_id: None,
+        }
+    }
+
+    #[test]
+    fn excludes_matching_entries() {
+        let filters = FeedFilters::new(vec![filter(
+            RssFilterKind::Keyword,
+            RssFilterAction::Exclude,
+            RssFilterTarget::Title,
+            "DLC",
+        )]);
+        assert!(!filters.allows(&entry("Free dlc for Game", None)));
+        assert!(filters.allows(&entry("Free Game", None)));
+    }
+
+    #[test]
+    fn requires_an_include_match_when_any_exist() {
+        let filters = FeedFilters::new(vec![
+            filter(
+                RssFilterKind::Regex,
+                RssFilterAction::Include,
+                RssFilterTarget::Link,
+                r"^https://store\.steampowered\.com/",
+            ),
+            filter(
+                RssFilterKind::Keyword,
+                RssFilterAction::Exclude,
+                RssFilterTarget::Title,
+                "region locked",
+            ),
+        ]);
+        assert!(filters.allows(&entry("Game", Some("https://store.steampowered.com/app/1"))));
+        assert!(!filters.allows(&entry("Game", Some("https://example.com"))));
+        assert!(!filters.allows(&entry("Game", None)));
+        assert!(!filters.allows(&entry(
+            "Game (Region Locked)",
+            Some("https://store.steampowered.com/app/1")
+        )));
+    }
+
+    #[test]
+    fn allows_everything_without_filters() {
+        assert!(FeedFilters::new(Vec::new()).allows(&entry("Anything", None)));
+    }
+
+    #[test]
+    fn mention_filters_only_affect_pings() {
+        let filters = FeedFilters::new(vec![filter(
+            RssFilterKind::Keyword,
+            RssFilterAction::Mention,
+            RssFilterTarget::Title,
+            "100%",
+        )]);
+        let discounted = entry("Game (75% off)", None);
+        assert!(filters.allows(&discounted));
+        assert!(!filters.mentions(&discounted));
+        assert!(filters.mentions(&entry("Game (100% off)", None)));
+        assert!(FeedFilters::new(Vec::new()).mentions(&discounted));
+    }
+}
@@ -1,8 +1,42 @@
 use sea_orm::sea_query::OnConflict;
+use sea_orm::sqlx::types::chrono::{TimeDelta, Utc};
 use sea_orm::{ExprTrait, QueryOrder, prelude::*};
 
 use crate::entities::enums::RssFeedStatus;
-use crate::entities::{prelude::*, rss_feed_entries, rss_feeds};
+use crate::entities::{prelude::*, rss_feed_entries, rss_feed_filters, rss_feeds};
+use crate::rss::FeedValidators;
+
+/// Changes to apply to a feed, leaving `None` fields as they are.
+#[derive(Debug, Default)]
+pub struct FeedEdit {
+    pub name: Option<String>,
+    pub channel_id: Option<u64>,
+    pub check_interval_minutes: Option<i32>,
+    /// `Some(None)` stops pinging a role.
+    pub mention_role_id: Option<Option<u64>>,
+    pub use_webhook: Option<bool>,
+}
+
+impl FeedEdit {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.channel_id.is_none()
+            && self.check_interval_minutes.is_none()
+            && self.mention_role_id.is_none()
+            && self.use_webhook.is_none()
+    }
+}
+
+/// Minutes to wait between checks of a feed that failed `failures` times in a row, doubling the
+/// interval per failure up to `max_backoff` (or the interval itself if that is longer).
+pub fn backoff_minutes(interval: i32, failures: i32, max_backoff: i32) -> i32 {
+    let factor = 1i32
+        .checked_shl(failures.clamp(0, 30) as u32)
+        .unwrap_or(i32::MAX);
+    interval
+        .saturating_mul(factor)
+        .min(max_backoff.max(interval))
+}
 
 pub struct RssManager {
     pool: DatabaseConnection,
@@ -45,7 +79,131 @@ impl RssManager {
         Ok(feed)
     }
 
-    pub async fn get_feeds_to_check(&self) -> Result<Vec<rss_feeds::Model>, DbErr> {
+    /// Applies `edit` to the feed, returning the updated feed if it exists.
+    pub async fn edit_feed(
+        &self,
+        id: Uuid,
+        edit: FeedEdit,
+    ) -> Result<Option<rss_feeds::Model>, DbErr> {
+        if edit.is_empty() {
+            return self.get_feed(id).await;
+        }
+
+        let mut query = RssFeeds::update_many();
+        if let Some(name) = edit.name {
+            query = query.col_expr(rss_feeds::Column::Name, Expr::value(name));
+        }
+        if let Some(channel_id) = edit.channel_id {
+            query = query.col_expr(rss_feeds::Column::ChannelId, Expr::value(channel_id as i64));
+        }
+        if let Some(role_id) = edit.mention_role_id {
+            query = query.col_expr(
+                rss_feeds::Column::MentionRoleId,
+                Expr::value(role_id.map(|id| id as i64)),
+            );
+        }
+        if let Some(use_webhook) = edit.use_webhook {
+            query = query.col_expr(rss_feeds::Column::UseWebhook, Expr::value(use_webhook));
+        }
+        // Forget the last body so the next check parses the feed again and picks up its icon.
+        if edit.use_webhook == Some(true) {
+            query = query
+                .col_expr(rss_feeds::Column::Etag, Expr::cust("NULL"))
+                .col_expr(rss_feeds::Column::LastModified, Expr::cust("NULL"))
+                .col_expr(rss_feeds::Column::ContentHash, Expr::cust("NULL"));
+        }
+        if let Some(minutes) = edit.check_interval_minutes {
+            query = query.col_expr(
+                rss_feeds::Column::CheckIntervalMinutes,
+                Expr::value(minutes),
+            );
+        }
+
+        let feed = query
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec_with_returning(&self.pool)
+            .await?
+            .into_iter()
+            .next();
+
+        Ok(feed)
+    }
+
+    /// Sets the status of the feed, returning the updated feed if it exists. Inactive feeds are
+    /// skipped by [`Self::get_feeds_to_check`], and reactivated feeds start with a clean slate.
+    pub async fn set_feed_status(
+        &self,
+        id: Uuid,
+        status: RssFeedStatus,
+    ) -> Result<Option<rss_feeds::Model>, DbErr> {
+        let mut query =
+            RssFeeds::update_many().col_expr(rss_feeds::Column::Status, status.as_enum());
+        if status == RssFeedStatus::Active {
+            query = query.col_expr(rss_feeds::Column::ConsecutiveFailures, Expr::value(0));
+        }
+
+        let feed = query
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec_with_returning(&self.pool)
+            .await?
+            .into_iter()
+            .next();
+
+        Ok(feed)
+    }
+
+    pub async fn add_feed_filter(
+        &self,
+        new_filter: rss_feed_filters::ActiveModel,
+    ) -> Result<rss_feed_filters::Model, DbErr> {
+        let filter = RssFeedFilters::insert(new_filter)
+            .exec_with_returning(&self.pool)
+            .await?;
+
+        Ok(filter)
+    }
+
+    /// Removes the filter if it belongs to a feed of `guild_id`, returning it.
+    pub async fn remove_feed_filter(
+        &self,
+        id: i32,
+        guild_id: u64,
+    ) -> Result<Option<rss_feed_filters::Model>, DbErr> {
+        let Some((filter, Some(_))) = RssFeedFilters::find_by_id(id)
+            .find_also_related(RssFeeds)
+            .filter(rss_feeds::Column::GuildId.eq(guild_id))
+            .one(&self.pool)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        RssFeedFilters::delete_by_id(filter.id)
+            .exec(&self.pool)
+            .await?;
+
+        Ok(Some(filter))
+    }
+
+    pub async fn list_feed_filters(
+        &self,
+        feed_id: Uuid,
+    ) -> Result<Vec<rss_feed_filters::Model>, DbErr> {
+        let filters = RssFeedFilters::find()
+            .filter(rss_feed_filters::Column::FeedId.eq(feed_id))
+            .order_by_asc(rss_feed_filters::Column::Id)
+            .all(&self.pool)
+            .await?;
+
+        Ok(filters)
+    }
+
+    /// Returns the active feeds due for a check, spacing out failing ones with
+    /// [`backoff_minutes`].
+    pub async fn get_feeds_to_check(
+        &self,
+        max_backoff: i32,
+    ) -> Result<Vec<rss_feeds::Model>, DbErr> {
         let feeds = RssFeeds::find()
             .filter(rss_feeds::Column::Status.eq(RssFeedStatus::Active))
             .filter(
@@ -58,9 +216,92 @@ impl RssManager {
             .all(&self.pool)
             .await?;
 
+        let now = Utc::now();
+        let feeds = feeds
+            .into_iter()
+            .filter(|feed| {
+                let wait = backoff_minutes(
+                    feed.check_interval_minutes,
+                    feed.consecutive_failures,
+                    max_backoff,
+                );
+                feed.last_checked_at.to_utc() + TimeDelta::minutes(i64::from(wait)) <= now
+            })
+            .collect();
+
         Ok(feeds)
     }
 
+    /// Stores the validators of the last processed body for the next conditional request.
+    pub async fn update_feed_validators(
+        &self,
+        id: Uuid,
+        validators: FeedValidators,
+    ) -> Result<(), DbErr> {
+        RssFeeds::update_many()
+            .col_expr(rss_feeds::Column::Etag, Expr::value(validators.etag))
+            .col_expr(
+                rss_feeds::Column::LastModified,
+                Expr::value(validators.last_modified),
+            )
+            .col_expr(
+                rss_feeds::Column::ContentHash,
+                Expr::value(validators.content_hash),
+            )
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_feed_icon_url(
+        &self,
+        id: Uuid,
+        icon_url: Option<String>,
+    ) -> Result<(), DbErr> {
+        RssFeeds::update_many()
+            .col_expr(rss_feeds::Column::IconUrl, Expr::value(icon_url))
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_feed_success(&self, id: Uuid) -> Result<(), DbErr> {
+        RssFeeds::update_many()
+            .col_expr(rss_feeds::Column::ConsecutiveFailures, Expr::value(0))
+            .col_expr(rss_feeds::Column::LastError, Expr::cust("NULL"))
+            .col_expr(rss_feeds::Column::LastSuccessAt, Expr::current_timestamp())
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts a failed check of the feed, returning the updated feed if it exists.
+    pub async fn record_feed_failure(
+        &self,
+        id: Uuid,
+        error: &str,
+    ) -> Result<Option<rss_feeds::Model>, DbErr> {
+        let feed = RssFeeds::update_many()
+            .col_expr(
+                rss_feeds::Column::ConsecutiveFailures,
+                Expr::col(rss_feeds::Column::ConsecutiveFailures).add(1),
+            )
+            .col_expr(rss_feeds::Column::LastError, Expr::value(error))
+            .filter(rss_feeds::Column::Id.eq(id))
+            .exec_with_returning(&self.pool)
+            .await?
+            .into_iter()
+            .next();
+
+        Ok(feed)
+    }
+
     pub async fn update_last_checked_at(&self, id: Uuid) -> Result<(), DbErr> {
         RssFeeds::update_many()
             .col_expr(rss_feeds::Column::LastCheckedAt, Expr::current_timestamp())
@@ -127,3 +368,17 @@ impl RssManager {
         Ok(feed_entries)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_minutes(5, 0, 1440), 5);
+        assert_eq!(backoff_minutes(5, 3, 1440), 40);
+        assert_eq!(backoff_minutes(5, 12, 1440), 1440);
+        assert_eq!(backoff_minutes(5, i32::MAX, 1440), 1440);
+        assert_eq!(backoff_minutes(2000, 1, 1440), 2000);
+    }
+}
@@ -1,14 +1,17 @@
 mod fetcher;
+mod filter;
 mod manager;
 mod scheduler;
 
 use serde::Deserialize;
 
 pub use fetcher::*;
+pub use filter::*;
 pub use manager::*;
 pub use scheduler::*;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RssConfig {
     pub settings: RssSettings,
     pub fetcher: RssFetcherConfig,
@@ -16,40 +19,62 @@ pub struct RssConfig {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RssSettings {
     pub default_check_interval: i32,
+    /// Bounds for per-feed check intervals set with `/rss edit`, in minutes.
+    pub min_check_interval: i32,
+    pub max_check_interval: i32,
+    /// Consecutive failed checks after which a feed is paused.
+    pub failure_threshold: i32,
+    /// Upper bound for the doubled check interval of a failing feed, in minutes.
+    pub max_backoff_minutes: i32,
     pub max_entries_per_check: usize,
     pub max_concurrent_checks: usize,
     pub debug_force_post: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RssFetcherConfig {
     pub http_timeout_seconds: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RssEmbedConfig {
     pub color: u32,
     pub max_description_length: usize,
 }
 
-impl Default for RssConfig {
+impl Default for RssSettings {
+    fn default() -> Self {
+        Self {
+            default_check_interval: 5,
+            min_check_interval: 1,
+            max_check_interval: 1440,
+            failure_threshold: 10,
+            max_backoff_minutes: 1440,
+            max_entries_per_check: 5,
+            max_concurrent_checks: 5,
+            debug_force_post: false,
+        }
+    }
+}
+
+impl Default for RssFetcherConfig {
+    fn default() -> Self {
+        Self {
+            http_timeout_seconds: 30,
+        }
+    }
+}
+
+impl Default for RssEmbedConfig {
     fn default() -> Self {
         Self {
-            settings: RssSettings {
-                default_check_interval: 5,
-                max_entries_per_check: 5,
-                max_concurrent_checks: 5,
-                debug_force_post: false,
-            },
-            fetcher: RssFetcherConfig {
-                http_timeout_seconds: 30,
-            },
-            embed: RssEmbedConfig {
-                color: 0x00D4AA,
-                max_description_length: 400,
-            },
+            color: 0x00D4AA,
+            max_description_length: 400,
         }
     }
 }
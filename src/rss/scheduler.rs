@@ -1,33 +1,63 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::StreamExt;
 use futures::stream::FuturesUnordered;
 use poise::serenity_prelude::{
-    Context, CreateEmbed, CreateEmbedFooter, CreateMessage, GenericChannelId, Timestamp,
-    async_trait, futures,
+    ChannelId, Color, Context, CreateAllowedMentions, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, CreateWebhook, ExecuteWebhook, GenericChannelId, MessageId, RoleId, Timestamp,
+    UserId, Webhook, async_trait, futures,
 };
 use sea_orm::TryIntoModel;
+use tokio::sync::Mutex;
+use tracing::warn;
 
 use crate::background_task::BackgroundTask;
+use crate::entities::enums::RssFeedStatus;
 use crate::entities::{rss_feed_entries, rss_feeds};
 use crate::error::Error;
-use crate::rss::{RssFetcher, RssManager};
+use crate::message::truncate;
+use crate::rss::{FeedFetch, FeedFilters, RssFetcher, RssManager};
 use crate::types::Data;
 
+const MAX_ERROR_LENGTH: usize = 500;
+/// Name of the webhook the bot creates in channels with webhook feeds.
+const WEBHOOK_NAME: &str = "RSS Feeds";
+const MAX_WEBHOOK_USERNAME_LENGTH: usize = 80;
+/// How long to post normally in a channel where the bot couldn't set up a webhook before trying
+/// again, in case it was given permissions since.
+const WEBHOOK_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Webhook the bot posts through in a channel, or when setting one up last failed.
+type ChannelWebhook = Result<Webhook, Instant>;
+
 pub struct RssScheduler {
     ctx: Context,
     rss_manager: RssManager,
+    /// Webhook of each channel with webhook feeds.
+    webhooks: Mutex<HashMap<ChannelId, ChannelWebhook>>,
 }
 
 impl RssScheduler {
     pub fn new(ctx: Context) -> Self {
         let rss_manager = RssManager::new(ctx.data::<Data>().pool.clone());
-        Self { ctx, rss_manager }
+        Self {
+            ctx,
+            rss_manager,
+            webhooks: Mutex::new(HashMap::new()),
+        }
     }
 
     async fn check_all_feeds(&self) -> Result<(), Error> {
-        let feeds = self.rss_manager.get_feeds_to_check().await?;
+        let max_backoff = self
+            .ctx
+            .data_ref::<Data>()
+            .config()
+            .rss
+            .settings
+            .max_backoff_minutes;
+        let feeds = self.rss_manager.get_feeds_to_check(max_backoff).await?;
 
         if feeds.is_empty() {
             return Ok(());
@@ -36,7 +66,8 @@ impl RssScheduler {
         let semaphore = Arc::new(tokio::sync::Semaphore::new(
             self.ctx
                 .data_ref::<Data>()
-                .rss_config
+                .config()
+                .rss
                 .settings
                 .max_concurrent_checks,
         ));
@@ -60,20 +91,65 @@ impl RssScheduler {
     }
 
     async fn check_single_feed(&self, feed: rss_feeds::Model) -> Result<(), Error> {
+        let config = self.ctx.data_ref::<Data>().config();
+        let fetcher = RssFetcher::new(&config.rss);
+
+        let fetched = fetcher.fetch_feed(&feed).await;
         let _ = self.rss_manager.update_last_checked_at(feed.id).await;
 
-        let data = self.ctx.data_ref::<Data>();
-        let fetcher = RssFetcher::new(&data.rss_config);
+        let (entries, validators, icon_url) = match fetched {
+            Ok(FeedFetch::NotModified(validators)) => {
+                self.rss_manager.record_feed_success(feed.id).await?;
+                if let Some(validators) = validators {
+                    self.rss_manager
+                        .update_feed_validators(feed.id, validators)
+                        .await?;
+                }
+                return Ok(());
+            }
+            Ok(FeedFetch::Modified {
+                entries,
+                validators,
+                icon_url,
+            }) => {
+                self.rss_manager.record_feed_success(feed.id).await?;
+                (entries, validators, icon_url)
+            }
+            Err(e) => {
+                warn!("Failed to fetch RSS feed {} ({}): {e}", feed.name, feed.url);
+                return self.handle_failure(&feed, &e.to_string()).await;
+            }
+        };
 
-        let Ok(entries) = fetcher.fetch_feed(&feed).await else {
-            return Ok(());
+        let feed = if feed.icon_url == icon_url {
+            feed
+        } else {
+            self.rss_manager
+                .update_feed_icon_url(feed.id, icon_url.clone())
+                .await?;
+            rss_feeds::Model { icon_url, ..feed }
         };
 
+        // Only remember the body once its entries are stored, so a failed run retries it.
+        self.process_entries(&feed, entries).await?;
+        self.rss_manager
+            .update_feed_validators(feed.id, validators)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn process_entries(
+        &self,
+        feed: &rss_feeds::Model,
+        entries: Vec<rss_feed_entries::ActiveModel>,
+    ) -> Result<(), Error> {
         if entries.is_empty() {
             return Ok(());
         }
 
-        let max_entries = data.rss_config.settings.max_entries_per_check;
+        let config = self.ctx.data_ref::<Data>().config();
+        let max_entries = config.rss.settings.max_entries_per_check;
 
         let entries: Vec<_> = if self
             .rss_manager
@@ -100,44 +176,98 @@ impl RssScheduler {
             }
         };
 
-        let entries_to_post: Vec<_> = if data.rss_config.settings.debug_force_post {
+        let filters = FeedFilters::new(self.rss_manager.list_feed_filters(feed.id).await?);
+
+        let entries: Vec<rss_feed_entries::Model> = if config.rss.settings.debug_force_post {
             entries
                 .into_iter()
                 .filter_map(|e| e.try_into_model().ok())
-                .collect::<Vec<_>>()
-                .into_iter()
-                .take(max_entries)
-                .rev()
                 .collect()
         } else {
-            let new_entries = self.rss_manager.insert_feed_entries(entries).await?;
-            if new_entries.is_empty() {
-                return Ok(());
-            }
-            new_entries.into_iter().take(max_entries).rev().collect()
+            // Entries are stored before filtering, so filtered ones won't be evaluated again.
+            self.rss_manager.insert_feed_entries(entries).await?
         };
 
+        let entries_to_post: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| filters.allows(entry))
+            .take(max_entries)
+            .rev()
+            .collect();
+
         for entry in entries_to_post {
-            self.post_entry_to_discord(&feed, entry).await?;
+            let mention = filters.mentions(&entry);
+            self.post_entry_to_discord(feed, entry, mention).await?;
             tokio::time::sleep(Duration::from_millis(500)).await;
         }
 
         Ok(())
     }
 
+    /// Records a failed check and pauses the feed once it reaches the failure threshold,
+    /// letting its creator know in the feed's channel.
+    async fn handle_failure(&self, feed: &rss_feeds::Model, error: &str) -> Result<(), Error> {
+        let error = truncate(error, MAX_ERROR_LENGTH);
+        let Some(feed) = self
+            .rss_manager
+            .record_feed_failure(feed.id, &error)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let threshold = self
+            .ctx
+            .data_ref::<Data>()
+            .config()
+            .rss
+            .settings
+            .failure_threshold;
+        if feed.status != RssFeedStatus::Active || feed.consecutive_failures < threshold {
+            return Ok(());
+        }
+
+        self.rss_manager
+            .set_feed_status(feed.id, RssFeedStatus::Inactive)
+            .await?;
+
+        let creator = UserId::new(feed.created_by as u64);
+        GenericChannelId::new(feed.channel_id as u64)
+            .send_message(
+                &self.ctx.http,
+                CreateMessage::new()
+                    .content(format!(
+                        "<@{creator}> The `{}` RSS feed was paused after {} failed checks in a \
+                         row. Use `/rss resume` once <{}> works again.",
+                        feed.name, feed.consecutive_failures, feed.url
+                    ))
+                    .add_embed(
+                        CreateEmbed::new()
+                            .title("Last error")
+                            .description(format!("```\n{error}\n```"))
+                            .color(Color::RED),
+                    )
+                    .allowed_mentions(CreateAllowedMentions::new().users(vec![creator])),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     async fn post_entry_to_discord(
         &self,
         feed: &rss_feeds::Model,
         entry: rss_feed_entries::Model,
+        mention: bool,
     ) -> Result<(), Error> {
         let timestamp = entry.published_at.unwrap_or(entry.created_at);
         let timestamp_str = timestamp.to_rfc3339();
-        let data = self.ctx.data_ref::<Data>();
+        let config = self.ctx.data_ref::<Data>().config();
 
         let mut embed =
             CreateEmbed::new()
                 .title(&entry.title)
-                .color(data.rss_config.embed.color)
+                .color(config.rss.embed.color)
                 .timestamp(Timestamp::parse(&timestamp_str).unwrap_or_else(|_| {
                     Timestamp::from_millis(timestamp.timestamp_millis()).unwrap()
                 }));
@@ -147,10 +277,10 @@ impl RssScheduler {
         }
 
         if let Some(description) = &entry.description {
-            embed = if description.len() > data.rss_config.embed.max_description_length {
+            embed = if description.len() > config.rss.embed.max_description_length {
                 embed.description(format!(
                     "{}...",
-                    &description[..data.rss_config.embed.max_description_length]
+                    &description[..config.rss.embed.max_description_length]
                 ))
             } else {
                 embed.description(description)
@@ -161,19 +291,128 @@ impl RssScheduler {
             embed = embed.image(thumbnail_url, None);
         }
 
-        embed = embed.footer(CreateEmbedFooter::new(format!("📡 {}", feed.name)));
+        // Only the feed's own role may be pinged, even if entry text contains other mentions.
+        let (content, allowed_mentions) = match feed.mention_role_id {
+            Some(role_id) if mention => {
+                let role_id = RoleId::new(role_id as u64);
+                (
+                    Some(format!("<@&{role_id}>")),
+                    CreateAllowedMentions::new().roles(vec![role_id]),
+                )
+            }
+            _ => (None, CreateAllowedMentions::new()),
+        };
 
-        let message = GenericChannelId::new(feed.channel_id as u64)
-            .send_message(&self.ctx.http, CreateMessage::new().add_embed(embed))
-            .await?;
+        let webhook_message_id = if feed.use_webhook {
+            self.post_with_webhook(
+                feed,
+                content.clone(),
+                embed.clone(),
+                allowed_mentions.clone(),
+            )
+            .await
+        } else {
+            None
+        };
+
+        let message_id = match webhook_message_id {
+            Some(message_id) => message_id,
+            None => {
+                let embed = embed.footer(CreateEmbedFooter::new(format!("📡 {}", feed.name)));
+                let mut message = CreateMessage::new()
+                    .add_embed(embed)
+                    .allowed_mentions(allowed_mentions);
+                if let Some(content) = content {
+                    message = message.content(content);
+                }
+
+                GenericChannelId::new(feed.channel_id as u64)
+                    .send_message(&self.ctx.http, message)
+                    .await?
+                    .id
+            }
+        };
 
         let _ = self
             .rss_manager
-            .update_entry_message_id(entry.id, message.id.get())
+            .update_entry_message_id(entry.id, message_id.get())
             .await;
 
         Ok(())
     }
+
+    /// Posts through the channel's webhook under the feed's name and icon. Returns `None` if
+    /// that isn't possible, so the entry is posted as a normal message instead.
+    async fn post_with_webhook(
+        &self,
+        feed: &rss_feeds::Model,
+        content: Option<String>,
+        embed: CreateEmbed<'_>,
+        allowed_mentions: CreateAllowedMentions<'_>,
+    ) -> Option<MessageId> {
+        let channel_id = ChannelId::new(feed.channel_id as u64);
+        let webhook = self.channel_webhook(channel_id).await?;
+
+        let mut execute = ExecuteWebhook::new()
+            .username(truncate(&feed.name, MAX_WEBHOOK_USERNAME_LENGTH))
+            .add_embed(embed)
+            .allowed_mentions(allowed_mentions);
+        if let Some(icon_url) = &feed.icon_url {
+            execute = execute.avatar_url(icon_url);
+        }
+        if let Some(content) = content {
+            execute = execute.content(content);
+        }
+
+        match webhook.execute(&self.ctx.http, true, execute).await {
+            Ok(message) => message.map(|message| message.id),
+            Err(e) => {
+                // The webhook may have been deleted, so look it up again next time.
+                warn!("Failed to post RSS feed {} with a webhook: {e}", feed.name);
+                self.webhooks.lock().await.remove(&channel_id);
+                None
+            }
+        }
+    }
+
+    /// Returns the bot's webhook in `channel_id`, creating it if the channel has none.
+    async fn channel_webhook(&self, channel_id: ChannelId) -> Option<Webhook> {
+        let mut webhooks = self.webhooks.lock().await;
+        match webhooks.get(&channel_id) {
+            Some(Ok(webhook)) => return Some(webhook.clone()),
+            Some(Err(failed_at)) if failed_at.elapsed() < WEBHOOK_RETRY_INTERVAL => return None,
+            _ => {}
+        }
+
+        let webhook = self.find_or_create_webhook(channel_id).await;
+        if let Err(e) = &webhook {
+            warn!("Can't use a webhook in channel {channel_id}, posting normally: {e}");
+        }
+        let webhook = webhook.map_err(|_| Instant::now());
+        webhooks.insert(channel_id, webhook.clone());
+
+        webhook.ok()
+    }
+
+    async fn find_or_create_webhook(&self, channel_id: ChannelId) -> Result<Webhook, Error> {
+        let bot_id = self.ctx.cache.current_user().id;
+        let existing = channel_id
+            .webhooks(&self.ctx.http)
+            .await?
+            .into_iter()
+            .find(|webhook| {
+                webhook.token.is_some() && webhook.user.as_ref().is_some_and(|u| u.id == bot_id)
+            });
+        if let Some(webhook) = existing {
+            return Ok(webhook);
+        }
+
+        let webhook = channel_id
+            .create_webhook(&self.ctx.http, CreateWebhook::new(WEBHOOK_NAME))
+            .await?;
+
+        Ok(webhook)
+    }
 }
 
 #[async_trait]
@@ -1,14 +1,15 @@
 use std::time::Duration;
 
-use poise::serenity_prelude::{Channel, Context, GenericChannelId, MessageId, async_trait};
+use poise::serenity_prelude::{
+    Channel, Context, GenericChannelId, GuildId, MessageId, async_trait,
+};
 use sea_orm::ExprTrait;
-use sea_orm::{QueryOrder, prelude::*};
+use sea_orm::{ActiveValue::*, QueryOrder, prelude::*};
 
 use crate::background_task::BackgroundTask;
-use crate::constants::FmhyChannel;
-use crate::constants::link_testing::ForumTag;
-use crate::entities::enums::WikiUrlStatus;
-use crate::entities::{prelude::*, wiki_urls};
+use crate::db::record_wiki_url_events;
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
 use crate::error::Error;
 use crate::types::Data;
 
@@ -33,6 +34,7 @@ impl BackgroundTask for StaleRemover {
     }
 
     async fn run(&mut self) {
+        let data = self.ctx.data_ref::<Data>();
         let Ok(entries) = WikiUrls::find()
             .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Pending))
             .filter(
@@ -40,7 +42,7 @@ impl BackgroundTask for StaleRemover {
                     .lt(Expr::current_timestamp().sub(Expr::cust("INTERVAL '1 day'"))),
             )
             .order_by_asc(wiki_urls::Column::CreatedAt)
-            .all(&self.ctx.data_ref::<Data>().pool)
+            .all(&data.pool)
             .await
         else {
             return;
@@ -51,8 +53,13 @@ impl BackgroundTask for StaleRemover {
                 continue;
             };
             let (cid, mid) = (cid as u64, mid as u64);
+            let config = data
+                .guild_config
+                .get(entry.guild_id.map(|g| GuildId::new(g as u64)), &data.pool)
+                .await;
+            let is_add_links = config.is(GuildSetting::AddLinks, cid);
 
-            if cid == FmhyChannel::ADD_LINKS
+            if is_add_links
                 && self
                     .ctx
                     .http
@@ -63,24 +70,38 @@ impl BackgroundTask for StaleRemover {
                 continue;
             }
 
-            if cid != FmhyChannel::ADD_LINKS
+            if !is_add_links
                 && let Ok(Channel::GuildThread(thread)) =
                     self.ctx.http.get_channel(GenericChannelId::new(cid)).await
             {
-                if thread.parent_id.get() != FmhyChannel::LINK_TESTING {
+                if !config.is(GuildSetting::LinkTesting, thread.parent_id.get()) {
                     continue;
                 }
 
-                if !thread
-                    .applied_tags
-                    .iter()
-                    .any(|t| matches!(t.get(), ForumTag::ADDED | ForumTag::REJECTED))
-                {
+                if !thread.applied_tags.iter().any(|t| {
+                    config.is_any(
+                        &[GuildSetting::AddedTag, GuildSetting::RejectedTag],
+                        t.get(),
+                    )
+                }) {
                     continue;
                 }
             }
 
-            let _ = entry.delete(&self.ctx.data_ref::<Data>().pool).await;
+            let pool = &data.pool;
+            let event = wiki_url_events::ActiveModel {
+                url: Set(entry.url.clone()),
+                old_status: Set(Some(entry.status)),
+                new_status: Set(None),
+                guild_id: Set(entry.guild_id),
+                channel_id: Set(entry.channel_id),
+                message_id: Set(entry.message_id),
+                ..Default::default()
+            };
+
+            if entry.delete(pool).await.is_ok() {
+                let _ = record_wiki_url_events(vec![event], pool).await;
+            }
         }
     }
 
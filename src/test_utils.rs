@@ -0,0 +1,55 @@
+//! Fixtures shared by tests that make real HTTP requests.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Installs the TLS backend reqwest clients are built with, as `main` does.
+pub fn install_crypto_provider() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+}
+
+/// Serves HTTP on `ip`, answering each connection with the raw response `respond` builds from
+/// the raw request. Returns the server's base URL.
+pub async fn stub_server_on<F, Fut>(ip: &str, respond: F) -> String
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = String> + Send,
+{
+    let listener = TcpListener::bind((ip, 0)).await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let respond = Arc::new(respond);
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let respond = Arc::clone(&respond);
+
+            tokio::spawn(async move {
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+
+                let response = respond(request).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    base
+}
+
+/// [`stub_server_on`] on the IPv4 loopback address.
+pub async fn stub_server<F, Fut>(respond: F) -> String
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = String> + Send,
+{
+    stub_server_on("127.0.0.1", respond).await
+}
+
+/// Path of a raw HTTP request.
+pub fn request_path(request: &str) -> Option<&str> {
+    request.split_whitespace().nth(1)
+}
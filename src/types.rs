@@ -1,11 +1,14 @@
 use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
 use sea_orm::DatabaseConnection;
 
-use crate::drama::DramaConfig;
+use crate::blocklist::BlocklistStore;
+use crate::config::Config;
 use crate::error::Error;
-use crate::rss::RssConfig;
+use crate::guild_config::GuildConfigStore;
+use crate::wiki::WikiSnapshotStore;
 
 pub type Context<'a> = poise::Context<'a, Data, Error>;
 pub type Command = poise::Command<Data, Error>;
@@ -14,6 +17,16 @@ pub struct Data {
     pub time_started: Instant,
     pub has_started: AtomicBool,
     pub pool: DatabaseConnection,
-    pub rss_config: RssConfig,
-    pub drama_config: DramaConfig,
+    pub guild_config: GuildConfigStore,
+    pub config: RwLock<Arc<Config>>,
+    pub wiki: WikiSnapshotStore,
+    pub blocklist: BlocklistStore,
+}
+
+impl Data {
+    /// Returns the current config. Hold on to it rather than calling this repeatedly, since a
+    /// reload can swap it at any point.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().unwrap().clone()
+    }
 }
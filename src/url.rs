@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::sync::LazyLock;
 
 use regex::Regex;
@@ -154,28 +155,308 @@ pub fn strip_tracking(input: impl AsRef<str>) -> Result<String, url::ParseError>
     Ok(url.into())
 }
 
-pub fn clean_url(url: &str) -> &str {
-    url.trim()
-        .trim_start_matches("https://")
-        .trim_start_matches("http://")
-        .trim_start_matches("www.")
-        .trim_end_matches("?tab=readme-ov-file")
-        .trim_end_matches('/')
+/// Path suffixes that point at a directory's default document.
+const INDEX_DOCUMENTS: &[&str] = &["index.htm", "index.html", "index.php"];
+
+/// Branch names GitHub and GitLab treat as the default view of a repository.
+const DEFAULT_BRANCHES: &[&str] = &["main", "master"];
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// Decodes percent-encoded unreserved characters and uppercases the remaining escapes.
+fn normalize_percent_encoding(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) if is_unreserved(byte) => out.push(byte as char),
+            Some(byte) => out.push_str(&format!("%{byte:02X}")),
+            None => {
+                let ch = input[i..].chars().next().unwrap_or_default();
+                out.push(ch);
+                i += ch.len_utf8();
+                continue;
+            }
+        }
+        i += 3;
+    }
+
+    out
+}
+
+/// Collapses repository URLs on GitHub and GitLab that all point at the same repository.
+fn normalize_forge_path(host: &str, path: &str) -> String {
+    let mut segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    let (is_github, is_gitlab) = (host == "github.com", host == "gitlab.com");
+    if !is_github && !is_gitlab {
+        return path.to_owned();
+    }
+
+    // GitLab routes repository views through a `/-/` segment, GitHub does not.
+    let view = if is_gitlab {
+        segments.iter().position(|s| s == "-")
+    } else {
+        (segments.len() > 2).then_some(2)
+    };
+
+    if let Some(view) = view {
+        let rest = &segments[view + usize::from(is_gitlab)..];
+        let is_default_view = match rest {
+            [kind, branch] => kind == "tree" && DEFAULT_BRANCHES.contains(&branch.as_str()),
+            [kind, branch, file] => {
+                kind == "blob"
+                    && DEFAULT_BRANCHES.contains(&branch.as_str())
+                    && file.eq_ignore_ascii_case("readme.md")
+            }
+            _ => false,
+        };
+        if is_default_view {
+            segments.truncate(view);
+        }
+    }
+
+    let repo_end = view.unwrap_or(segments.len());
+    if let Some(repo) = segments[..repo_end].last_mut()
+        && let Some(stripped) = repo.strip_suffix(".git")
+    {
+        *repo = stripped.to_owned();
+    }
+
+    if is_github {
+        for segment in segments.iter_mut().take(2) {
+            *segment = segment.to_lowercase();
+        }
+    }
+
+    let mut normalized = String::with_capacity(path.len());
+    for segment in &segments {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    normalized
+}
+
+/// Canonicalizes a URL into the scheme-less form stored in `wiki_urls`.
+///
+/// Hosts are lowercased and IDNA-encoded, default ports, `www.`, index documents, trailing
+/// slashes, fragments and tracking parameters are dropped, percent-encoding is normalized,
+/// query parameters are sorted and repository URLs on GitHub/GitLab are collapsed.
+pub fn clean_url(url: &str) -> String {
+    let url = url.trim();
+    let parsed = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{url}"))
+    };
+
+    let Ok(mut parsed) = parsed.map_err(|_| ()).and_then(|u| {
+        (matches!(u.scheme(), "http" | "https") && u.host_str().is_some())
+            .then_some(u)
+            .ok_or(())
+    }) else {
+        return url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_start_matches("www.")
+            .trim_end_matches('/')
+            .to_owned();
+    };
+
+    strip_tracking_params(&mut parsed);
+
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| {
+            let rest = host.strip_prefix("ww")?;
+            let digits = rest.find('.')?;
+            rest[..digits]
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| &rest[digits + 1..])
+        })
+        .unwrap_or(host)
+        .trim_end_matches('.')
+        .to_owned();
+
+    let mut canonical = host.clone();
+    if let Some(port) = parsed.port().filter(|p| !matches!(p, 80 | 443)) {
+        canonical.push_str(&format!(":{port}"));
+    }
+
+    let mut path = normalize_forge_path(&host, &normalize_percent_encoding(parsed.path()));
+    for index in INDEX_DOCUMENTS {
+        if let Some(stripped) = path.strip_suffix(index)
+            && stripped.ends_with('/')
+        {
+            path.truncate(stripped.len());
+            break;
+        }
+    }
+    canonical.push_str(path.trim_end_matches('/'));
+
+    let mut pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(k, v)| !(k == "tab" && v.ends_with("-ov-file")))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if !pairs.is_empty() {
+        pairs.sort();
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+        for (k, v) in &pairs {
+            serializer.append_pair(k, v);
+        }
+        canonical.push('?');
+        canonical.push_str(&serializer.finish());
+    }
+
+    // Hash-based routes are part of the page address, plain anchors are not.
+    if let Some(fragment) = parsed.fragment().filter(|f| f.starts_with(['/', '!'])) {
+        canonical.push('#');
+        canonical.push_str(fragment);
+    }
+
+    canonical
+}
+
+/// Public suffixes spanning two labels, under which the third label is the registrant.
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "ac.uk", "co.in", "co.jp", "co.kr", "co.nz", "co.uk", "co.za", "com.ar", "com.au", "com.br",
+    "com.cn", "com.hk", "com.mx", "com.sg", "com.tr", "com.tw", "net.au", "org.au", "org.uk",
+];
+
+/// Hosting platforms where every subdomain belongs to a different site.
+const SHARED_HOST_SUFFIXES: &[&str] = &[
+    "blogspot.com",
+    "carrd.co",
+    "github.io",
+    "gitlab.io",
+    "glitch.me",
+    "itch.io",
+    "neocities.org",
+    "netlify.app",
+    "notion.site",
+    "pages.dev",
+    "substack.com",
+    "tumblr.com",
+    "vercel.app",
+    "web.app",
+    "wordpress.com",
+];
+
+/// Hosts where the leading path segments, rather than the host, identify the site.
+const PATH_SCOPED_HOSTS: &[(&str, usize)] = &[
+    ("codeberg.org", 1),
+    ("github.com", 1),
+    ("gitlab.com", 1),
+    ("huggingface.co", 1),
+    ("reddit.com", 2),
+    ("sourceforge.net", 2),
+    ("t.me", 1),
+];
+
+fn last_labels(host: &str, count: usize) -> &str {
+    host.rmatch_indices('.')
+        .nth(count - 1)
+        .map_or(host, |(i, _)| &host[i + 1..])
+}
+
+/// Returns the registrable part of `host`, e.g. `example.co.uk` for `docs.example.co.uk`.
+///
+/// Subdomains of shared hosting platforms are kept, since they are unrelated sites.
+pub fn registrable_domain(host: &str) -> &str {
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return host;
+    }
+
+    let has_suffix = |suffix: &str| {
+        host.strip_suffix(suffix)
+            .is_some_and(|rest| rest.ends_with('.'))
+    };
+
+    if let Some(suffix) = SHARED_HOST_SUFFIXES.iter().find(|s| has_suffix(s)) {
+        last_labels(host, suffix.split('.').count() + 1)
+    } else if MULTI_LABEL_SUFFIXES.iter().any(|s| has_suffix(s)) {
+        last_labels(host, 3)
+    } else {
+        last_labels(host, 2)
+    }
+}
+
+/// Splits a cleaned URL into its lowercase host (without `www.` or port) and the rest.
+fn split_host(url: &str) -> Option<(String, &str)> {
+    let rest = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .trim_start_matches("www.");
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let host = rest[..end]
+        .rsplit_once(':')
+        .map_or(&rest[..end], |(h, _)| h);
+
+    (!host.is_empty()).then(|| (host.to_lowercase(), &rest[end..]))
+}
+
+/// Returns a key identifying the site a URL belongs to, so that different pages and
+/// subdomains of the same site can be recognized as such.
+pub fn site_key(url: &str) -> Option<String> {
+    let (host, path) = split_host(url)?;
+
+    if let Some(&(_, depth)) = PATH_SCOPED_HOSTS.iter().find(|(h, _)| *h == host) {
+        let segments: Vec<&str> = path
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .take(depth)
+            .collect();
+
+        return Some(if segments.len() == depth {
+            format!("{host}/{}", segments.join("/").to_lowercase())
+        } else {
+            host
+        });
+    }
+
+    Some(registrable_domain(&host).to_owned())
 }
 
 static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(https?):\/\/(?:ww(?:w|\d+)\.)?((?:[\w_-]+(?:\.[\w_-]+)+)[\w.,@?^=%&:\/~+#-]*[\w@?^=%&~+-])").unwrap()
 });
 
+/// Removes every link from `text`, leaving the words around them.
+pub fn strip_urls(text: &str) -> String {
+    URL_RE.replace_all(text, "").into_owned()
+}
+
+/// Returns the distinct canonical links in `haystack`, in order of first appearance.
 pub fn extract_urls(haystack: &str) -> Option<Vec<String>> {
+    let mut seen = HashSet::new();
     let matches: Vec<String> = URL_RE
         .find_iter(haystack)
         .filter_map(|m| {
             let cleaned = strip_tracking(m.as_str()).ok()?;
             let url = clean_url(&cleaned);
             (!url.starts_with("discord.com/channels") && !url.starts_with("fmhy.net"))
-                .then_some(url.to_owned())
+                .then_some(url)
         })
+        .filter(|url| seen.insert(url.clone()))
         .collect();
 
     Some(matches).filter(|m| !m.is_empty())
@@ -222,4 +503,128 @@ mod tests {
         assert_eq!(a, b);
         assert_eq!(a, "https://example.com/x");
     }
+
+    #[test]
+    fn clean_url_normalizes_host_port_and_path() {
+        assert_eq!(clean_url("https://EXAMPLE.com"), "example.com");
+        assert_eq!(clean_url("http://www.example.com/"), "example.com");
+        assert_eq!(clean_url("https://example.com:443/"), "example.com");
+        assert_eq!(clean_url("http://example.com:80/a"), "example.com/a");
+        assert_eq!(
+            clean_url("https://example.com:8080/a"),
+            "example.com:8080/a"
+        );
+        assert_eq!(clean_url("https://example.com/index.html"), "example.com");
+        assert_eq!(
+            clean_url("https://example.com/docs/index.php"),
+            "example.com/docs"
+        );
+        assert_eq!(clean_url("https://ww2.example.com/x"), "example.com/x");
+        assert_eq!(clean_url("example.com/page/"), "example.com/page");
+    }
+
+    #[test]
+    fn clean_url_normalizes_encoding_and_idn() {
+        assert_eq!(
+            clean_url("https://example.com/%7euser"),
+            "example.com/~user"
+        );
+        assert_eq!(clean_url("https://example.com/a%2fb"), "example.com/a%2Fb");
+        assert_eq!(clean_url("https://bücher.de/"), "xn--bcher-kva.de");
+        assert_eq!(clean_url("https://xn--bcher-kva.de"), "xn--bcher-kva.de");
+    }
+
+    #[test]
+    fn clean_url_sorts_query_and_drops_anchors() {
+        assert_eq!(
+            clean_url("https://example.com/s?b=2&a=1&utm_source=x"),
+            "example.com/s?a=1&b=2"
+        );
+        assert_eq!(
+            clean_url("https://example.com/page#section"),
+            "example.com/page"
+        );
+        assert_eq!(clean_url("https://example.com/#/app"), "example.com#/app");
+    }
+
+    #[test]
+    fn clean_url_collapses_repository_variants() {
+        for variant in [
+            "https://github.com/Owner/Repo",
+            "https://github.com/owner/repo/",
+            "https://github.com/owner/repo.git",
+            "https://github.com/owner/repo?tab=readme-ov-file",
+            "https://github.com/owner/repo#readme",
+            "https://github.com/owner/repo/tree/main",
+            "https://github.com/owner/repo/blob/master/README.md",
+        ] {
+            assert_eq!(clean_url(variant), "github.com/owner/repo", "{variant}");
+        }
+        assert_eq!(
+            clean_url("https://github.com/owner/repo/blob/main/docs/Guide.md"),
+            "github.com/owner/repo/blob/main/docs/Guide.md"
+        );
+        assert_eq!(
+            clean_url("https://gitlab.com/group/project/-/tree/master"),
+            "gitlab.com/group/project"
+        );
+        assert_eq!(
+            clean_url("https://gitlab.com/group/project.git"),
+            "gitlab.com/group/project"
+        );
+    }
+
+    #[test]
+    fn site_suffix_lists_are_sorted() {
+        assert!(MULTI_LABEL_SUFFIXES.windows(2).all(|w| w[0] < w[1]));
+        assert!(SHARED_HOST_SUFFIXES.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn site_key_ignores_subdomains_and_paths() {
+        assert_eq!(site_key("example.com").as_deref(), Some("example.com"));
+        assert_eq!(
+            site_key("example.com/some/page").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            site_key("docs.example.com:8080/x").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            site_key("https://www.Example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            site_key("shop.example.co.uk").as_deref(),
+            Some("example.co.uk")
+        );
+    }
+
+    #[test]
+    fn site_key_keeps_shared_hosts_and_path_scoped_sites_apart() {
+        assert_eq!(
+            site_key("foo.github.io/x").as_deref(),
+            Some("foo.github.io")
+        );
+        assert_ne!(site_key("foo.github.io"), site_key("bar.github.io"));
+        assert_eq!(
+            site_key("github.com/Owner/repo").as_deref(),
+            Some("github.com/owner")
+        );
+        assert_ne!(site_key("github.com/a/x"), site_key("github.com/b/x"));
+        assert_eq!(
+            site_key("reddit.com/r/foo/wiki").as_deref(),
+            Some("reddit.com/r/foo")
+        );
+        assert_eq!(site_key("github.com").as_deref(), Some("github.com"));
+    }
+
+    #[test]
+    fn extract_urls_dedupes_in_order() {
+        assert_eq!(
+            extract_urls("https://b.com https://a.com https://www.b.com/").unwrap(),
+            ["b.com", "a.com"]
+        );
+    }
 }
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use super::collect_heading_text;
+use crate::url::clean_url;
+
+/// A heading in the wiki, linked to the heading it is nested under.
+pub struct WikiSection {
+    pub title: String,
+    pub level: usize,
+    pub parent: Option<usize>,
+}
+
+/// A single list item in the wiki, usually one site with its mirrors.
+pub struct WikiEntry {
+    /// Index into [`WikiIndex::sections`], `None` for items above the first heading.
+    pub section: Option<usize>,
+    pub name: String,
+    /// Canonical form of every external link in the item.
+    pub urls: Vec<String>,
+    pub description: String,
+    /// Raw markdown of the item's first line, without the list marker.
+    pub source: String,
+    /// Marked with ⭐ as a community favourite.
+    pub starred: bool,
+    /// Marked with 🌐 as an index of other sites.
+    pub is_index: bool,
+    /// Marked with ↪️ as a pointer to another section.
+    pub is_redirect: bool,
+}
+
+impl WikiEntry {
+    /// The entry's marker emojis, followed by a space if there are any.
+    pub fn markers(&self) -> String {
+        let mut markers = String::new();
+        for (set, marker) in [
+            (self.starred, "⭐"),
+            (self.is_index, "🌐"),
+            (self.is_redirect, "↪️"),
+        ] {
+            if set {
+                markers.push_str(marker);
+            }
+        }
+        if !markers.is_empty() {
+            markers.push(' ');
+        }
+
+        markers
+    }
+}
+
+/// Sections and entries of the single-page wiki.
+#[derive(Default)]
+pub struct WikiIndex {
+    pub sections: Vec<WikiSection>,
+    pub entries: Vec<WikiEntry>,
+    by_url: HashMap<String, Vec<usize>>,
+}
+
+#[derive(Default)]
+struct EntryBuilder {
+    start: usize,
+    text: String,
+    name: Option<String>,
+    link_text: Option<String>,
+    urls: Vec<String>,
+}
+
+impl WikiIndex {
+    pub fn parse(content: &str) -> Self {
+        let mut index = Self::default();
+        let mut current_section: Option<usize> = None;
+        let mut stack: Vec<EntryBuilder> = Vec::new();
+        let mut parser_iter = Parser::new(content).into_offset_iter();
+
+        while let Some((event, range)) = parser_iter.next() {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    let title = collect_heading_text(&mut parser_iter)
+                        .replace(['►', '▷'], "")
+                        .trim()
+                        .to_owned();
+                    let level = level as usize;
+
+                    let mut parent = current_section;
+                    while let Some(id) = parent
+                        && index.sections[id].level >= level
+                    {
+                        parent = index.sections[id].parent;
+                    }
+
+                    index.sections.push(WikiSection {
+                        title,
+                        level,
+                        parent,
+                    });
+                    current_section = Some(index.sections.len() - 1);
+                }
+                Event::Start(Tag::Item) => stack.push(EntryBuilder {
+                    start: range.start,
+                    ..Default::default()
+                }),
+                Event::End(TagEnd::Item) => {
+                    if let Some(builder) = stack.pop() {
+                        index.push_entry(content, current_section, builder);
+                    }
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    if let Some(builder) = stack.last_mut() {
+                        if dest_url.starts_with("http://") || dest_url.starts_with("https://") {
+                            builder.urls.push(clean_url(&dest_url));
+                        }
+                        builder.link_text = Some(String::new());
+                    }
+                }
+                Event::End(TagEnd::Link) => {
+                    if let Some(builder) = stack.last_mut()
+                        && let Some(link_text) = builder.link_text.take()
+                        && builder.name.is_none()
+                    {
+                        builder.name = Some(link_text);
+                    }
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some(builder) = stack.last_mut() {
+                        builder.text.push_str(&text);
+                        if let Some(link_text) = &mut builder.link_text {
+                            link_text.push_str(&text);
+                        }
+                    }
+                }
+                Event::SoftBreak | Event::HardBreak => {
+                    if let Some(builder) = stack.last_mut() {
+                        builder.text.push(' ');
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        index
+    }
+
+    fn push_entry(&mut self, content: &str, section: Option<usize>, builder: EntryBuilder) {
+        let Some(name) = builder.name else {
+            return;
+        };
+
+        let line_end = content[builder.start..]
+            .find('\n')
+            .map_or(content.len(), |i| builder.start + i);
+        let line = &content[builder.start..line_end];
+        let source = line
+            .trim_start()
+            .trim_start_matches(['*', '-', '+'])
+            .trim_start();
+
+        let id = self.entries.len();
+        for url in &builder.urls {
+            let ids = self.by_url.entry(url.clone()).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+
+        self.entries.push(WikiEntry {
+            section,
+            name: name.trim().to_owned(),
+            description: builder
+                .text
+                .split_once(" - ")
+                .map(|(_, description)| description.trim().to_owned())
+                .unwrap_or_default(),
+            source: source.to_owned(),
+            starred: builder.text.contains('⭐'),
+            is_index: builder.text.contains('🌐'),
+            is_redirect: builder.text.contains('↪'),
+            urls: builder.urls,
+        });
+    }
+
+    /// Returns the entries linking to `url`, which must already be canonical.
+    pub fn entries_for_url(&self, url: &str) -> impl Iterator<Item = &WikiEntry> {
+        self.by_url
+            .get(url)
+            .into_iter()
+            .flatten()
+            .map(|&id| &self.entries[id])
+    }
+
+    /// Returns the heading titles leading to `entry`, outermost first.
+    pub fn section_path(&self, entry: &WikiEntry) -> Vec<&str> {
+        let mut path = Vec::new();
+        let mut section = entry.section;
+
+        while let Some(id) = section {
+            path.push(self.sections[id].title.as_str());
+            section = self.sections[id].parent;
+        }
+        path.reverse();
+
+        path
+    }
+
+    /// Formats where `entry` lives, e.g. `**Section** / **Subsection** ► ⭐ Name`.
+    pub fn format_location(&self, entry: &WikiEntry) -> String {
+        let path = self.section_path(entry);
+        if path.is_empty() {
+            format!("{}{}", entry.markers(), entry.name)
+        } else {
+            format!(
+                "**{}** ► {}{}",
+                path.join("** / **"),
+                entry.markers(),
+                entry.name
+            )
+        }
+    }
+
+    /// Lists the sections each of `urls` appears under, skipping URLs that aren't in the wiki.
+    pub fn format_locations<'a>(
+        &self,
+        urls: impl IntoIterator<Item = &'a str>,
+        max_lines: usize,
+    ) -> Option<String> {
+        let mut lines = Vec::new();
+
+        for url in urls {
+            for entry in self.entries_for_url(url) {
+                let line = format!("- {url} → {}", self.format_location(entry));
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        let total = lines.len();
+        let mut formatted = lines[..total.min(max_lines)].join("\n");
+        if total > max_lines {
+            let _ = write!(formatted, "\n*…and {} more*", total - max_lines);
+        }
+
+        Some(formatted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIKI: &str = "\
+# ► Streaming
+
+## ▷ Movies
+
+* ⭐ **[Site A](https://www.site-a.com/)**, [2](https://mirror-a.net) - Movies and TV
+* [Site B](https://site-b.org)
+* 🌐 **[Index](https://index.io)** - List of sites
+
+## Music
+
+* ↪️ **[Audio Tools](https://fmhy.net/audio)**
+
+# Reading
+
+* [Site A](https://site-a.com) - Books too
+";
+
+    fn entry<'a>(index: &'a WikiIndex, name: &str) -> &'a WikiEntry {
+        index.entries.iter().find(|e| e.name == name).unwrap()
+    }
+
+    #[test]
+    fn nests_sections_by_heading_level() {
+        let index = WikiIndex::parse(WIKI);
+
+        let titles: Vec<_> = index.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Streaming", "Movies", "Music", "Reading"]);
+        assert_eq!(
+            index.section_path(entry(&index, "Index")),
+            ["Streaming", "Movies"]
+        );
+        assert_eq!(
+            index.section_path(entry(&index, "Audio Tools")),
+            ["Streaming", "Music"]
+        );
+        assert_eq!(index.section_path(&index.entries[4]), ["Reading"]);
+    }
+
+    #[test]
+    fn collects_every_url_of_an_entry() {
+        let index = WikiIndex::parse(WIKI);
+        let site = &index.entries[0];
+
+        assert_eq!(site.name, "Site A");
+        assert_eq!(site.urls, ["site-a.com", "mirror-a.net"]);
+        assert_eq!(entry(&index, "Audio Tools").urls, ["fmhy.net/audio"]);
+    }
+
+    #[test]
+    fn reads_markers_and_descriptions() {
+        let index = WikiIndex::parse(WIKI);
+
+        let site = &index.entries[0];
+        assert!(site.starred && !site.is_index && !site.is_redirect);
+        assert_eq!(site.description, "Movies and TV");
+        assert_eq!(
+            index.format_location(site),
+            "**Streaming** / **Movies** ► ⭐ Site A"
+        );
+
+        let plain = entry(&index, "Site B");
+        assert!(!plain.starred && !plain.is_index && !plain.is_redirect);
+        assert_eq!(plain.description, "");
+        assert_eq!(plain.markers(), "");
+
+        assert!(entry(&index, "Index").is_index);
+        assert_eq!(entry(&index, "Index").description, "List of sites");
+        assert!(entry(&index, "Audio Tools").is_redirect);
+    }
+
+    #[test]
+    fn maps_urls_to_every_entry_linking_them() {
+        let index = WikiIndex::parse(WIKI);
+
+        let sections: Vec<_> = index
+            .entries_for_url("site-a.com")
+            .map(|e| index.section_path(e))
+            .collect();
+        assert_eq!(sections, [vec!["Streaming", "Movies"], vec!["Reading"]]);
+        assert_eq!(index.entries_for_url("mirror-a.net").count(), 1);
+        assert_eq!(index.entries_for_url("unknown.com").count(), 0);
+    }
+}
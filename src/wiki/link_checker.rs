@@ -0,0 +1,583 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use poise::serenity_prelude::{
+    Color, Context, CreateEmbed, CreateEmbedFooter, CreateMessage, GenericChannelId, async_trait,
+    futures,
+};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::sqlx::types::chrono::{self, Utc};
+use sea_orm::{ActiveValue::*, QueryOrder, QuerySelect, prelude::*};
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+use url::Url;
+
+use super::LinkCheckConfig;
+use crate::background_task::BackgroundTask;
+use crate::db::{ChunkSize, get_task_last_run, record_task_run};
+use crate::entities::enums::WikiUrlStatus;
+use crate::entities::{prelude::*, wiki_url_checks, wiki_urls};
+use crate::error::Error;
+use crate::message::truncate;
+use crate::types::Data;
+use crate::url::clean_url;
+
+/// Name the dead link report's last run is stored under.
+const REPORT_TASK: &str = "dead_link_report";
+const REPORT_INTERVAL_DAYS: i64 = 7;
+/// Discord rejects embeds over 6000 characters, which five full fields and their names exceed.
+const FIELDS_PER_EMBED: usize = 4;
+const MAX_FIELD_LENGTH: usize = 1024;
+/// Room kept in a field for the note about the lines that didn't fit.
+const MORE_NOTE_LENGTH: usize = 24;
+
+/// Outcome of requesting a single link.
+#[derive(Debug)]
+pub struct ProbeResult {
+    pub status_code: Option<u16>,
+    /// Canonical URL the link redirected to, if it ended up somewhere else.
+    pub redirect_url: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ProbeResult {
+    /// Bot protection and rate limiting still mean the site is up, so they count as alive.
+    pub fn is_alive(&self) -> bool {
+        self.status_code
+            .is_some_and(|code| code < 400 || matches!(code, 401 | 403 | 405 | 429))
+    }
+}
+
+/// Requests links with bounded concurrency, never hitting the same host twice at once.
+pub struct LinkProber {
+    client: reqwest::Client,
+    max_concurrent_hosts: usize,
+    host_delay: Duration,
+}
+
+impl LinkProber {
+    pub fn new(config: &LinkCheckConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.http_timeout_seconds))
+            .user_agent(env!("CARGO_PKG_NAME"))
+            .build()
+            .expect("HTTP client creation failed");
+
+        Self {
+            client,
+            max_concurrent_hosts: config.max_concurrent_hosts,
+            host_delay: Duration::from_millis(config.host_delay_millis),
+        }
+    }
+
+    /// Stored URLs are canonical and have no scheme, so they're requested over HTTPS.
+    fn request_url(url: &str) -> Cow<'_, str> {
+        if url.contains("://") {
+            Cow::Borrowed(url)
+        } else {
+            Cow::Owned(format!("https://{url}"))
+        }
+    }
+
+    pub async fn probe(&self, url: &str) -> ProbeResult {
+        let url = Self::request_url(url);
+
+        match self.client.get(url.as_ref()).send().await {
+            Ok(response) => {
+                let final_url = clean_url(response.url().as_str());
+                ProbeResult {
+                    status_code: Some(response.status().as_u16()),
+                    redirect_url: (final_url != clean_url(&url)).then_some(final_url),
+                    error: None,
+                }
+            }
+            Err(e) => ProbeResult {
+                status_code: e.status().map(|s| s.as_u16()),
+                redirect_url: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Probes every URL, checking different hosts concurrently and each host's links one at a
+    /// time with a delay in between.
+    pub async fn probe_all(&self, urls: Vec<String>) -> Vec<(String, ProbeResult)> {
+        let mut by_host: HashMap<String, Vec<String>> = HashMap::new();
+        for url in urls {
+            let host = Url::parse(&Self::request_url(&url))
+                .ok()
+                .and_then(|u| u.host_str().map(ToOwned::to_owned))
+                .unwrap_or_default();
+            by_host.entry(host).or_default().push(url);
+        }
+
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_hosts));
+        let mut tasks = FuturesUnordered::new();
+
+        for urls in by_host.into_values() {
+            let sem = Arc::clone(&semaphore);
+
+            let task = async move {
+                let _permit = sem.acquire().await.unwrap();
+                let mut results = Vec::with_capacity(urls.len());
+
+                for (i, url) in urls.into_iter().enumerate() {
+                    if i > 0 {
+                        tokio::time::sleep(self.host_delay).await;
+                    }
+                    let result = self.probe(&url).await;
+                    results.push((url, result));
+                }
+
+                results
+            };
+
+            tasks.push(task);
+        }
+
+        let mut results = Vec::new();
+        while let Some(host_results) = tasks.next().await {
+            results.extend(host_results);
+        }
+
+        results
+    }
+}
+
+/// Joins as many `lines` as fit in an embed field, noting how many were left out.
+fn field_value(lines: &[String]) -> String {
+    let mut value = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        // Unless this is the last line, keep room for the note about the ones after it.
+        let budget = if i + 1 == lines.len() {
+            MAX_FIELD_LENGTH
+        } else {
+            MAX_FIELD_LENGTH - MORE_NOTE_LENGTH
+        };
+        let line = if value.is_empty() {
+            truncate(line, budget)
+        } else {
+            format!("\n{line}")
+        };
+
+        if value.chars().count() + line.chars().count() > budget {
+            value.push_str(&format!("\n*…and {} more*", lines.len() - i));
+            break;
+        }
+        value.push_str(&line);
+    }
+
+    value
+}
+
+/// Periodically probes Added wiki URLs and reports the ones that keep failing.
+pub struct LinkChecker {
+    ctx: Context,
+}
+
+impl LinkChecker {
+    pub fn new(ctx: Context) -> Self {
+        Self { ctx }
+    }
+
+    /// Whether a week has passed since the last report, which is kept in the database so
+    /// restarts neither repeat nor skip it.
+    async fn report_due(&self) -> Result<bool, Error> {
+        let pool = &self.ctx.data_ref::<Data>().pool;
+        let last_report = get_task_last_run(REPORT_TASK, pool).await?;
+        let due_before = Utc::now() - chrono::Duration::days(REPORT_INTERVAL_DAYS);
+
+        Ok(last_report.is_none_or(|at| at.to_utc() <= due_before))
+    }
+
+    async fn check_links(&self) -> Result<(), Error> {
+        let data = self.ctx.data_ref::<Data>();
+        let config = data.config();
+        let config = &config.wiki.link_check;
+
+        let urls: Vec<String> = WikiUrls::find()
+            .select_only()
+            .column(wiki_urls::Column::Url)
+            .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Added))
+            .into_tuple()
+            .all(&data.pool)
+            .await?;
+        let checks: HashMap<String, wiki_url_checks::Model> = WikiUrlChecks::find()
+            .all(&data.pool)
+            .await?
+            .into_iter()
+            .map(|check| (check.url.clone(), check))
+            .collect();
+
+        let recheck_before = Utc::now() - chrono::Duration::hours(config.recheck_hours as i64);
+        let mut due: Vec<String> = urls
+            .into_iter()
+            .filter(|url| {
+                checks
+                    .get(url)
+                    .is_none_or(|check| check.last_checked_at < recheck_before)
+            })
+            .collect();
+        due.sort_by_key(|url| checks.get(url).map(|check| check.last_checked_at));
+        due.truncate(config.batch_size);
+
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        // Built per run so reloaded timeouts and concurrency limits apply.
+        let results = LinkProber::new(config).probe_all(due).await;
+        let failing = results.iter().filter(|(_, r)| !r.is_alive()).count();
+
+        let models: Vec<_> = results
+            .into_iter()
+            .map(|(url, result)| {
+                let previous = checks.get(&url);
+                let alive = result.is_alive();
+
+                wiki_url_checks::ActiveModel {
+                    consecutive_failures: Set(if alive {
+                        0
+                    } else {
+                        previous.map_or(0, |c| c.consecutive_failures) + 1
+                    }),
+                    status_code: Set(result.status_code.map(i32::from)),
+                    redirect_url: Set(result.redirect_url),
+                    last_error: Set(result.error),
+                    last_checked_at: Set(now.into()),
+                    last_success_at: Set(if alive {
+                        Some(now.into())
+                    } else {
+                        previous.and_then(|c| c.last_success_at)
+                    }),
+                    url: Set(url),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let checked = models.len();
+
+        for chunk in models.chunks(WikiUrlChecks::chunk_size()) {
+            WikiUrlChecks::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::column(wiki_url_checks::Column::Url)
+                        .update_columns([
+                            wiki_url_checks::Column::ConsecutiveFailures,
+                            wiki_url_checks::Column::StatusCode,
+                            wiki_url_checks::Column::RedirectUrl,
+                            wiki_url_checks::Column::LastError,
+                            wiki_url_checks::Column::LastCheckedAt,
+                            wiki_url_checks::Column::LastSuccessAt,
+                        ])
+                        .to_owned(),
+                )
+                .exec(&data.pool)
+                .await?;
+        }
+
+        info!("Checked {checked} wiki links, {failing} failing");
+
+        Ok(())
+    }
+
+    async fn send_report(&self) -> Result<(), Error> {
+        let data = self.ctx.data_ref::<Data>();
+        let config = data.config();
+        let Some(channel_id) = config.wiki.log_channel_id else {
+            return Ok(());
+        };
+        let threshold = config.wiki.link_check.failure_threshold;
+
+        let suspects = WikiUrlChecks::find()
+            .filter(wiki_url_checks::Column::ConsecutiveFailures.gte(threshold))
+            .order_by_desc(wiki_url_checks::Column::ConsecutiveFailures)
+            .all(&data.pool)
+            .await?;
+        if suspects.is_empty() {
+            info!("No suspected dead wiki links to report");
+            return Ok(());
+        }
+
+        let still_added: HashSet<String> = WikiUrls::find()
+            .select_only()
+            .column(wiki_urls::Column::Url)
+            .filter(wiki_urls::Column::Url.is_in(suspects.iter().map(|c| c.url.as_str())))
+            .filter(wiki_urls::Column::Status.eq(WikiUrlStatus::Added))
+            .into_tuple()
+            .all(&data.pool)
+            .await?
+            .into_iter()
+            .collect();
+        let suspects: Vec<_> = suspects
+            .into_iter()
+            .filter(|c| still_added.contains(&c.url))
+            .collect();
+
+        if suspects.is_empty() {
+            info!("No suspected dead wiki links to report");
+            return Ok(());
+        }
+
+        let snapshot = data.wiki.get().await?;
+        let mut sections: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for check in &suspects {
+            let section = snapshot
+                .index
+                .entries_for_url(&check.url)
+                .next()
+                .and_then(|e| snapshot.index.section_path(e).first().copied())
+                .unwrap_or("Not in the wiki");
+            let reason = match (check.status_code, &check.redirect_url) {
+                (Some(code), Some(redirect)) => format!("`{code}` via {redirect}"),
+                (Some(code), None) => format!("`{code}`"),
+                (None, _) => "no response".to_owned(),
+            };
+            sections.entry(section).or_default().push(format!(
+                "- {} ({reason}, failed {}×)",
+                check.url, check.consecutive_failures
+            ));
+        }
+
+        let sections: Vec<_> = sections.into_iter().collect();
+        for (i, chunk) in sections.chunks(FIELDS_PER_EMBED).enumerate() {
+            let mut embed = CreateEmbed::new().color(Color::RED);
+            if i == 0 {
+                embed = embed.title("Suspected dead links").description(format!(
+                    "{} Added links failed at least {threshold} checks in a row.",
+                    suspects.len()
+                ));
+            }
+
+            for (section, lines) in chunk {
+                embed = embed.field(*section, field_value(lines), false);
+            }
+
+            embed = embed.footer(CreateEmbedFooter::new(format!(
+                "Wiki snapshot {}",
+                snapshot.short_hash()
+            )));
+
+            GenericChannelId::new(channel_id)
+                .send_message(&self.ctx.http, CreateMessage::new().embed(embed))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for LinkChecker {
+    async fn init(ctx: Context) -> Result<Self, Error> {
+        Ok(Self::new(ctx))
+    }
+
+    fn interval(&mut self) -> Duration {
+        Duration::from_mins(
+            self.ctx
+                .data_ref::<Data>()
+                .config()
+                .wiki
+                .link_check
+                .interval_minutes,
+        )
+    }
+
+    async fn run(&mut self) {
+        if let Err(e) = self.check_links().await {
+            warn!("Failed to check wiki links: {e}");
+        }
+
+        match self.report_due().await {
+            Ok(true) => {
+                // Recorded up front so a report that fails halfway isn't resent every run.
+                let pool = &self.ctx.data_ref::<Data>().pool;
+                if let Err(e) = record_task_run(REPORT_TASK, pool).await {
+                    warn!("Failed to record dead link report: {e}");
+                } else if let Err(e) = self.send_report().await {
+                    warn!("Failed to send dead link report: {e}");
+                }
+            }
+            Ok(false) => {}
+            Err(e) => warn!("Failed to look up the last dead link report: {e}"),
+        }
+    }
+
+    fn timeout(&mut self) -> Option<Duration> {
+        Some(Duration::from_mins(30))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::test_utils;
+
+    /// Counts how many requests are handled at once.
+    #[derive(Default)]
+    struct Concurrency {
+        active: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl Concurrency {
+        fn enter(&self) {
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(now, Ordering::SeqCst);
+        }
+
+        fn exit(&self) {
+            self.active.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn max(&self) -> usize {
+            self.max.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Serves canned responses on `ip`, counting requests in each of `counters`.
+    async fn stub_server_on(ip: &str, counters: Vec<Arc<Concurrency>>) -> String {
+        let counters = Arc::new(counters);
+
+        test_utils::stub_server_on(ip, move |request| {
+            let counters = Arc::clone(&counters);
+            async move {
+                counters.iter().for_each(|c| c.enter());
+                let status = match test_utils::request_path(&request) {
+                    Some("/moved") => "301 Moved Permanently\r\nLocation: /ok",
+                    Some("/gone") => "404 Not Found",
+                    Some("/protected") => "403 Forbidden",
+                    _ => "200 OK",
+                };
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                counters.iter().for_each(|c| c.exit());
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            }
+        })
+        .await
+    }
+
+    async fn stub_server() -> (String, Arc<Concurrency>) {
+        let concurrency = Arc::new(Concurrency::default());
+        let base = stub_server_on("127.0.0.1", vec![Arc::clone(&concurrency)]).await;
+
+        (base, concurrency)
+    }
+
+    fn prober() -> LinkProber {
+        test_utils::install_crypto_provider();
+        LinkProber::new(&LinkCheckConfig {
+            host_delay_millis: 10,
+            http_timeout_seconds: 5,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn classifies_responses() {
+        let (base, _) = stub_server().await;
+        let prober = prober();
+
+        let ok = prober.probe(&format!("{base}/ok")).await;
+        assert!(ok.is_alive());
+        assert_eq!(ok.status_code, Some(200));
+        assert_eq!(ok.redirect_url, None);
+
+        let gone = prober.probe(&format!("{base}/gone")).await;
+        assert!(!gone.is_alive());
+        assert_eq!(gone.status_code, Some(404));
+
+        assert!(prober.probe(&format!("{base}/protected")).await.is_alive());
+    }
+
+    #[tokio::test]
+    async fn records_redirect_targets() {
+        let (base, _) = stub_server().await;
+        let moved = prober().probe(&format!("{base}/moved")).await;
+
+        assert!(moved.is_alive());
+        assert_eq!(moved.status_code, Some(200));
+        assert_eq!(moved.redirect_url, Some(clean_url(&format!("{base}/ok"))));
+    }
+
+    #[tokio::test]
+    async fn reports_unreachable_hosts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let result = prober().probe(&format!("http://{addr}/")).await;
+        assert!(!result.is_alive());
+        assert_eq!(result.status_code, None);
+        assert!(result.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn probes_one_link_per_host_at_a_time() {
+        let (base, max_active) = stub_server().await;
+        let urls: Vec<String> = (0..5).map(|i| format!("{base}/ok?page={i}")).collect();
+
+        let results = prober().probe_all(urls.clone()).await;
+
+        assert_eq!(results.len(), urls.len());
+        assert!(results.iter().all(|(_, r)| r.is_alive()));
+        assert_eq!(max_active.max(), 1);
+    }
+
+    #[tokio::test]
+    async fn bounds_the_number_of_hosts_probed_at_once() {
+        let overall = Arc::new(Concurrency::default());
+        let mut hosts = Vec::new();
+        let mut urls = Vec::new();
+        // Every loopback address is a separate host to the prober.
+        for ip in ["127.0.0.1", "127.0.0.2", "127.0.0.3", "127.0.0.4"] {
+            let host = Arc::new(Concurrency::default());
+            let base = stub_server_on(ip, vec![Arc::clone(&overall), Arc::clone(&host)]).await;
+            urls.extend((0..3).map(|i| format!("{base}/ok?page={i}")));
+            hosts.push(host);
+        }
+
+        test_utils::install_crypto_provider();
+        let prober = LinkProber::new(&LinkCheckConfig {
+            host_delay_millis: 10,
+            http_timeout_seconds: 5,
+            max_concurrent_hosts: 2,
+            ..Default::default()
+        });
+        let results = prober.probe_all(urls.clone()).await;
+
+        assert_eq!(results.len(), urls.len());
+        assert!(results.iter().all(|(_, r)| r.is_alive()));
+        assert_eq!(overall.max(), 2);
+        assert!(hosts.iter().all(|host| host.max() == 1));
+    }
+
+    #[test]
+    fn fits_report_fields_in_discord_limits() {
+        let lines: Vec<String> = (0..100)
+            .map(|i| format!("- example.com/{} (`404`, failed 3×)", "a".repeat(i)))
+            .collect();
+
+        let value = field_value(&lines);
+        assert!(value.chars().count() <= MAX_FIELD_LENGTH);
+        assert!(value.starts_with(&lines[0]));
+        assert!(value.ends_with(" more*"));
+
+        let long = vec!["x".repeat(2000)];
+        assert_eq!(field_value(&long).chars().count(), MAX_FIELD_LENGTH);
+
+        let short = vec!["- a".to_owned(), "- b".to_owned()];
+        assert_eq!(field_value(&short), "- a\n- b");
+    }
+}
@@ -0,0 +1,105 @@
+mod index;
+mod link_checker;
+mod reconciler;
+mod search;
+mod snapshot;
+
+use pulldown_cmark::{CowStr, Event, Parser, Tag, TagEnd};
+use serde::Deserialize;
+
+pub use index::*;
+pub use link_checker::*;
+pub use reconciler::*;
+pub use search::*;
+pub use snapshot::*;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WikiConfig {
+    /// Guild the wiki belongs to. Reconciler changes are recorded under it, and its channel
+    /// settings apply to tracked links that weren't posted in a guild.
+    pub guild_id: Option<u64>,
+    pub snapshot_refresh_minutes: u64,
+    pub reconcile_interval_minutes: u64,
+    /// How long the reconciler leaves entries alone after they change, giving the wiki time to
+    /// catch up with the channels.
+    pub reconcile_grace_hours: u64,
+    /// Status changes above which a reconciliation is aborted instead of applied.
+    pub max_reconcile_changes: usize,
+    pub log_channel_id: Option<u64>,
+    pub link_check: LinkCheckConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinkCheckConfig {
+    pub interval_minutes: u64,
+    /// How many links to probe per run.
+    pub batch_size: usize,
+    /// How long before a link is probed again.
+    pub recheck_hours: u64,
+    pub max_concurrent_hosts: usize,
+    /// Pause between two requests to the same host.
+    pub host_delay_millis: u64,
+    pub http_timeout_seconds: u64,
+    /// Consecutive failures before a link shows up in the dead link report.
+    pub failure_threshold: i32,
+}
+
+impl Default for WikiConfig {
+    fn default() -> Self {
+        Self {
+            guild_id: None,
+            snapshot_refresh_minutes: 10,
+            reconcile_interval_minutes: 60,
+            reconcile_grace_hours: 24,
+            max_reconcile_changes: 100,
+            log_channel_id: None,
+            link_check: LinkCheckConfig::default(),
+        }
+    }
+}
+
+impl Default for LinkCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 60,
+            batch_size: 200,
+            recheck_hours: 24,
+            max_concurrent_hosts: 8,
+            host_delay_millis: 1000,
+            http_timeout_seconds: 15,
+            failure_threshold: 3,
+        }
+    }
+}
+
+pub fn collect_wiki_urls<'a>(content: &'a str) -> Vec<CowStr<'a>> {
+    let parser = Parser::new(content);
+    let mut urls = Vec::new();
+
+    for event in parser {
+        if let Event::Start(Tag::Link { dest_url, .. }) = event {
+            urls.push(dest_url);
+        }
+    }
+
+    urls
+}
+
+fn collect_heading_text<'a, I>(parser: &mut I) -> String
+where
+    I: Iterator<Item = (Event<'a>, std::ops::Range<usize>)>,
+{
+    let mut text = String::new();
+
+    for (event, _) in parser.by_ref() {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::End(TagEnd::Heading(_)) => break,
+            _ => {}
+        }
+    }
+
+    text
+}
@@ -0,0 +1,488 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    Color, Context, CreateEmbed, CreateEmbedFooter, CreateMessage, GenericChannelId, GuildId,
+    async_trait,
+};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::sqlx::types::chrono::{self, DateTime, Utc};
+use sea_orm::{ActiveValue::*, QueryOrder, TransactionTrait, prelude::*};
+use tracing::{info, warn};
+
+use crate::background_task::BackgroundTask;
+use crate::db::{ChunkSize, record_wiki_url_events};
+use crate::entities::enums::{GuildSetting, WikiUrlStatus};
+use crate::entities::{prelude::*, wiki_url_events, wiki_urls};
+use crate::error::Error;
+use crate::guild_config::GuildConfig;
+use crate::types::Data;
+use crate::wiki::{WikiConfig, WikiSnapshot};
+
+/// NSFW links live outside the main wiki, so their status can't be derived from it.
+const NSFW_SETTINGS: [GuildSetting; 3] = [
+    GuildSetting::NsfwAddLinks,
+    GuildSetting::NsfwRecentlyAdded,
+    GuildSetting::NsfwRemoved,
+];
+
+/// Differences between the tracked `wiki_urls` and the links present in the wiki.
+#[derive(Default)]
+pub struct WikiUrlDiff {
+    /// Pending or removed entries that are in the wiki.
+    pub newly_added: Vec<wiki_urls::Model>,
+    /// Added entries that are no longer in the wiki.
+    pub newly_removed: Vec<wiki_urls::Model>,
+    /// Wiki links that aren't tracked at all.
+    pub untracked: Vec<String>,
+}
+
+impl WikiUrlDiff {
+    pub fn is_empty(&self) -> bool {
+        self.newly_added.is_empty() && self.newly_removed.is_empty() && self.untracked.is_empty()
+    }
+
+    /// Number of tracked entries whose status would change.
+    pub fn status_changes(&self) -> usize {
+        self.newly_added.len() + self.newly_removed.len()
+    }
+}
+
+/// Compares `entries` with the links in the wiki, skipping those `is_nsfw` says are NSFW. Entries
+/// changed after `settled_before` are left alone, since the wiki may not reflect what was just
+/// posted in the channels yet.
+pub fn diff_wiki_urls(
+    entries: Vec<wiki_urls::Model>,
+    wiki_urls: &HashSet<String>,
+    settled_before: DateTime<Utc>,
+    is_nsfw: impl Fn(&wiki_urls::Model) -> bool,
+) -> WikiUrlDiff {
+    let mut diff = WikiUrlDiff::default();
+    let mut tracked = HashSet::with_capacity(entries.len());
+
+    for entry in entries {
+        tracked.insert(entry.url.clone());
+
+        if entry.updated_at > settled_before || is_nsfw(&entry) {
+            continue;
+        }
+
+        match (entry.status, wiki_urls.contains(&entry.url)) {
+            (WikiUrlStatus::Added, false) => diff.newly_removed.push(entry),
+            (WikiUrlStatus::Pending | WikiUrlStatus::Removed, true) => diff.newly_added.push(entry),
+            _ => {}
+        }
+    }
+
+    diff.untracked = wiki_urls
+        .iter()
+        .filter(|url| !tracked.contains(*url))
+        .cloned()
+        .collect();
+    diff.untracked.sort_unstable();
+
+    diff
+}
+
+/// Latest change time of the entries [`diff_wiki_urls`] may judge against `snapshot`.
+pub fn settled_before(config: &WikiConfig, snapshot: &WikiSnapshot) -> DateTime<Utc> {
+    let grace = chrono::Duration::hours(config.reconcile_grace_hours as i64);
+    snapshot.fetched_at.min(Utc::now() - grace)
+}
+
+/// Channel settings of the guilds tracked entries were posted in, falling back to the wiki's
+/// guild for entries without one.
+pub struct EntryGuildConfigs {
+    wiki_guild_id: Option<i64>,
+    configs: HashMap<Option<i64>, Arc<GuildConfig>>,
+}
+
+impl EntryGuildConfigs {
+    pub async fn load(data: &Data, entries: &[wiki_urls::Model]) -> Self {
+        let wiki_guild_id = data.config().wiki.guild_id.map(|id| id as i64);
+        let mut configs = HashMap::new();
+        for guild_id in entries.iter().map(|e| e.guild_id.or(wiki_guild_id)) {
+            if let Entry::Vacant(slot) = configs.entry(guild_id) {
+                slot.insert(
+                    data.guild_config
+                        .get(guild_id.map(|id| GuildId::new(id as u64)), &data.pool)
+                        .await,
+                );
+            }
+        }
+
+        Self {
+            wiki_guild_id,
+            configs,
+        }
+    }
+
+    /// Whether `entry` was posted in one of its guild's NSFW channels.
+    pub fn is_nsfw(&self, entry: &wiki_urls::Model) -> bool {
+        entry.channel_id.is_some_and(|cid| {
+            self.configs
+                .get(&entry.guild_id.or(self.wiki_guild_id))
+                .is_some_and(|config| config.is_any(&NSFW_SETTINGS, cid as u64))
+        })
+    }
+}
+
+/// Periodically brings `wiki_urls` in line with the live wiki.
+pub struct WikiReconciler {
+    ctx: Context,
+}
+
+impl WikiReconciler {
+    pub fn new(ctx: Context) -> Self {
+        Self { ctx }
+    }
+
+    async fn reconcile(&self) -> Result<(), Error> {
+        let data = self.ctx.data_ref::<Data>();
+        let config = data.config();
+        let snapshot = data.wiki.get().await?;
+
+        if snapshot.urls.is_empty() {
+            warn!("Skipping wiki reconciliation, the snapshot has no links");
+            return Ok(());
+        }
+
+        let entries = WikiUrls::find()
+            .order_by_desc(wiki_urls::Column::UpdatedAt)
+            .all(&data.pool)
+            .await?;
+        let guild_configs = EntryGuildConfigs::load(data, &entries).await;
+        let diff = diff_wiki_urls(
+            entries,
+            &snapshot.urls,
+            settled_before(&config.wiki, &snapshot),
+            |entry| guild_configs.is_nsfw(entry),
+        );
+
+        if diff.is_empty() {
+            return Ok(());
+        }
+
+        let max_changes = config.wiki.max_reconcile_changes;
+        if diff.status_changes() > max_changes {
+            warn!(
+                "Aborting wiki reconciliation, it would change the status of {} links",
+                diff.status_changes()
+            );
+            if let Some(channel_id) = config.wiki.log_channel_id {
+                let embed = summary_embed(&diff)
+                    .title("Wiki reconciliation aborted")
+                    .description(format!(
+                        "This run would change the status of {} links, more than the limit of \
+                         {max_changes}. Nothing was changed; raise `wiki.max_reconcile_changes` \
+                         if this is expected.",
+                        diff.status_changes()
+                    ))
+                    .color(Color::RED);
+
+                GenericChannelId::new(channel_id)
+                    .send_message(&self.ctx.http, CreateMessage::new().embed(embed))
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        let txn = data.pool.begin().await?;
+        let mut events = Vec::new();
+
+        for (entries, status) in [
+            (&diff.newly_added, WikiUrlStatus::Added),
+            (&diff.newly_removed, WikiUrlStatus::Removed),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+
+            for chunk in entries.chunks(WikiUrls::chunk_size()) {
+                let mut query = WikiUrls::update_many()
+                    .col_expr(wiki_urls::Column::Status, status.as_enum())
+                    .col_expr(wiki_urls::Column::UpdatedAt, Expr::current_timestamp());
+                // Removal reasons only go away when a link comes back.
+                if status == WikiUrlStatus::Added {
+                    query = query
+                        .col_expr(wiki_urls::Column::RemovalReason, Expr::cust("NULL"))
+                        .col_expr(wiki_urls::Column::RemovalCategory, Expr::cust("NULL"));
+                }
+
+                query
+                    .filter(wiki_urls::Column::Id.is_in(chunk.iter().map(|e| e.id)))
+                    .exec(&txn)
+                    .await?;
+            }
+
+            events.extend(entries.iter().map(|e| wiki_url_events::ActiveModel {
+                url: Set(e.url.clone()),
+                old_status: Set(Some(e.status)),
+                new_status: Set(Some(status)),
+                guild_id: Set(wiki_guild_id),
+                ..Default::default()
+            }));
+        }
+
+        for chunk in diff.untracked.chunks(WikiUrls::chunk_size()) {
+            let inserted = WikiUrls::insert_many(chunk.iter().map(|url| wiki_urls::ActiveModel {
+                url: Set(url.clone()),
+                guild_id: Set(wiki_guild_id),
+                created_at: Set(Utc::now().into()),
+                updated_at: Set(Utc::now().into()),
+                status: Set(WikiUrlStatus::Added),
+                ..Default::default()
+            }))
+            .on_conflict(
+                OnConflict::column(wiki_urls::Column::Url)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_with_returning(&txn)
+            .await?;
+
+            events.extend(inserted.into_iter().map(|e| wiki_url_events::ActiveModel {
+                url: Set(e.url),
+                old_status: Set(None),
+                new_status: Set(Some(WikiUrlStatus::Added)),
+                guild_id: Set(wiki_guild_id),
+                ..Default::default()
+            }));
+        }
+
+        for chunk in events.chunks(WikiUrlEvents::chunk_size()) {
+            record_wiki_url_events(chunk.to_vec(), &txn).await?;
+        }
+
+        txn.commit().await?;
+
+        info!(
+            "Reconciled wiki URLs: {} added, {} removed, {} newly tracked",
+            diff.newly_added.len(),
+            diff.newly_removed.len(),
+            diff.untracked.len()
+        );
+
+        if let Some(channel_id) = config.wiki.log_channel_id {
+            let embed = summary_embed(&diff).footer(CreateEmbedFooter::new(format!(
+                "Wiki snapshot {}",
+                snapshot.short_hash()
+            )));
+
+            GenericChannelId::new(channel_id)
+                .send_message(&self.ctx.http, CreateMessage::new().embed(embed))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists up to `max_lines` URLs, noting how many were left out.
+fn format_url_list<'a>(urls: impl ExactSizeIterator<Item = &'a str>, max_lines: usize) -> String {
+    let total = urls.len();
+    let mut lines = String::new();
+
+    for url in urls.take(max_lines) {
+        if !lines.is_empty() {
+            lines.push('\n');
+        }
+        let _ = write!(lines, "- {url}");
+    }
+    if total > max_lines {
+        let _ = write!(lines, "\n*…and {} more*", total - max_lines);
+    }
+
+    lines
+}
+
+fn summary_embed(diff: &WikiUrlDiff) -> CreateEmbed<'static> {
+    let mut embed = CreateEmbed::new()
+        .title("Wiki reconciliation")
+        .color(Color::BLUE);
+
+    for (title, urls) in [
+        (
+            "Pending/Removed → Added",
+            format_url_list(diff.newly_added.iter().map(|e| e.url.as_str()), 15),
+        ),
+        (
+            "Added → Removed",
+            format_url_list(diff.newly_removed.iter().map(|e| e.url.as_str()), 15),
+        ),
+        (
+            "Newly tracked",
+            format_url_list(diff.untracked.iter().map(String::as_str), 15),
+        ),
+    ] {
+        if !urls.is_empty() {
+            embed = embed.field(title, urls, false);
+        }
+    }
+
+    embed
+}
+
+#[async_trait]
+impl BackgroundTask for WikiReconciler {
+    async fn init(ctx: Context) -> Result<Self, Error> {
+        Ok(Self::new(ctx))
+    }
+
+    fn interval(&mut self) -> Duration {
+        Duration::from_mins(
+            self.ctx
+                .data_ref::<Data>()
+                .config()
+                .wiki
+                .reconcile_interval_minutes,
+        )
+    }
+
+    async fn run(&mut self) {
+        if let Err(e) = self.reconcile().await {
+            warn!("Failed to reconcile wiki URLs: {e}");
+        }
+    }
+
+    fn timeout(&mut self) -> Option<Duration> {
+        Some(Duration::from_mins(10))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::FmhyChannel;
+
+    /// Judges entries by the FMHY channels, as guilds without settings do.
+    fn is_nsfw(entry: &wiki_urls::Model) -> bool {
+        entry
+            .channel_id
+            .is_some_and(|cid| GuildConfig::default().is_any(&NSFW_SETTINGS, cid as u64))
+    }
+
+    fn entry(url: &str, status: WikiUrlStatus, channel_id: u64) -> wiki_urls::Model {
+        wiki_urls::Model {
+            id: 0,
+            url: url.to_owned(),
+            channel_id: Some(channel_id as i64),
+            user_id: None,
+            message_id: None,
+            guild_id: None,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            status,
+            removal_reason: None,
+            removal_category: None,
+            submitted_by: None,
+        }
+    }
+
+    fn wiki(urls: &[&str]) -> HashSet<String> {
+        urls.iter().map(|url| (*url).to_owned()).collect()
+    }
+
+    fn urls(entries: &[wiki_urls::Model]) -> Vec<&str> {
+        entries.iter().map(|e| e.url.as_str()).collect()
+    }
+
+    #[test]
+    fn flips_statuses_to_match_the_wiki() {
+        let diff = diff_wiki_urls(
+            vec![
+                entry(
+                    "gone.com",
+                    WikiUrlStatus::Added,
+                    FmhyChannel::RECENTLY_ADDED,
+                ),
+                entry(
+                    "kept.com",
+                    WikiUrlStatus::Added,
+                    FmhyChannel::RECENTLY_ADDED,
+                ),
+                entry("new.com", WikiUrlStatus::Pending, FmhyChannel::ADD_LINKS),
+                entry(
+                    "back.com",
+                    WikiUrlStatus::Removed,
+                    FmhyChannel::REMOVE_SITES,
+                ),
+                entry(
+                    "still-gone.com",
+                    WikiUrlStatus::Removed,
+                    FmhyChannel::REMOVE_SITES,
+                ),
+            ],
+            &wiki(&["kept.com", "new.com", "back.com"]),
+            Utc::now(),
+            is_nsfw,
+        );
+
+        assert_eq!(urls(&diff.newly_removed), ["gone.com"]);
+        assert_eq!(urls(&diff.newly_added), ["new.com", "back.com"]);
+        assert!(diff.untracked.is_empty());
+        assert_eq!(diff.status_changes(), 3);
+    }
+
+    #[test]
+    fn lists_untracked_wiki_links_in_order() {
+        let diff = diff_wiki_urls(
+            vec![entry(
+                "a.com",
+                WikiUrlStatus::Added,
+                FmhyChannel::RECENTLY_ADDED,
+            )],
+            &wiki(&["c.com", "a.com", "b.com"]),
+            Utc::now(),
+            is_nsfw,
+        );
+
+        assert_eq!(diff.untracked, ["b.com", "c.com"]);
+        assert_eq!(diff.status_changes(), 0);
+    }
+
+    #[test]
+    fn skips_nsfw_entries() {
+        let diff = diff_wiki_urls(
+            vec![
+                entry(
+                    "nsfw.com",
+                    WikiUrlStatus::Added,
+                    FmhyChannel::NSFW_RECENTLY_ADDED,
+                ),
+                entry(
+                    "nsfw.net",
+                    WikiUrlStatus::Pending,
+                    FmhyChannel::NSFW_ADD_LINKS,
+                ),
+            ],
+            &wiki(&["nsfw.net"]),
+            Utc::now(),
+            is_nsfw,
+        );
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn skips_entries_changed_after_the_cutoff() {
+        let mut removed = entry(
+            "gone.com",
+            WikiUrlStatus::Removed,
+            FmhyChannel::REMOVE_SITES,
+        );
+        removed.updated_at = Utc::now().into();
+
+        let diff = diff_wiki_urls(
+            vec![removed],
+            &wiki(&["gone.com"]),
+            Utc::now() - chrono::Duration::hours(1),
+            is_nsfw,
+        );
+
+        assert!(diff.is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+use std::cmp::Ordering;
+
+use super::{WikiEntry, WikiIndex};
+use crate::url::clean_url;
+
+const NAME_WEIGHT: f32 = 3.0;
+const HEADING_WEIGHT: f32 = 1.5;
+const DESCRIPTION_WEIGHT: f32 = 1.0;
+const URL_WEIGHT: f32 = 1.0;
+const STARRED_BONUS: f32 = 1.1;
+
+/// A parsed search query, e.g. `torrent client section:linux -nsfw`.
+#[derive(Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    /// Entries containing any of these words are left out (`-word`).
+    pub excluded: Vec<String>,
+    /// Entries must be under a heading containing each of these (`section:word`).
+    pub sections: Vec<String>,
+    /// Entries must link to one of these domains or their subdomains (`domain:example.com`).
+    pub domains: Vec<String>,
+    /// Canonical form of the whole query, for looking up pasted links.
+    pub url: String,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self {
+            url: clean_url(query.trim()),
+            ..Default::default()
+        };
+
+        for word in split_words(query) {
+            if let Some(section) = word.strip_prefix("section:") {
+                parsed.sections.push(section.to_lowercase());
+            } else if let Some(domain) = word.strip_prefix("domain:") {
+                let domain = if domain.contains("://") {
+                    clean_url(domain)
+                } else {
+                    clean_url(&format!("https://{domain}"))
+                };
+                let host = domain.split('/').next().unwrap_or_default();
+                parsed.domains.push(host.to_owned());
+            } else if let Some(excluded) = word.strip_prefix('-')
+                && !excluded.is_empty()
+            {
+                parsed.excluded.push(excluded.to_lowercase());
+            } else {
+                parsed.terms.extend(tokenize(&word));
+            }
+        }
+
+        parsed.sections.retain(|s| !s.is_empty());
+        parsed.domains.retain(|d| !d.is_empty());
+
+        parsed
+    }
+
+    fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+            && self.excluded.is_empty()
+            && self.sections.is_empty()
+            && self.domains.is_empty()
+    }
+}
+
+/// Splits on whitespace, keeping double-quoted phrases (e.g. `section:"video streaming"`) together.
+fn split_words(query: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How many typos a term of this length may contain.
+fn max_distance(term: &str) -> usize {
+    match term.chars().count() {
+        0..4 => 0,
+        4..8 => 1,
+        _ => 2,
+    }
+}
+
+/// Edit distance between `a` and `b`, counting adjacent transpositions as one edit, or `None`
+/// if it exceeds `limit`.
+fn bounded_distance(a: &str, b: &str, limit: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > limit {
+        return None;
+    }
+
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 0..a.len() {
+        current[0] = i + 1;
+
+        for j in 0..b.len() {
+            let cost = usize::from(a[i] != b[j]);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                current[j + 1] = current[j + 1].min(before_previous[j - 1] + 1);
+            }
+        }
+
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[b.len()];
+    (distance <= limit).then_some(distance)
+}
+
+/// Scores how well `term` matches one of `tokens`, from 0 (no match) to 1 (exact match).
+fn match_quality(term: &str, tokens: &[String]) -> f32 {
+    let limit = max_distance(term);
+    let mut best: f32 = 0.0;
+
+    for token in tokens {
+        let quality = if token == term {
+            1.0
+        } else if term.len() >= 3 && token.starts_with(term) {
+            0.75
+        } else {
+            match bounded_distance(term, token, limit) {
+                Some(1) => 0.5,
+                Some(2) => 0.35,
+                _ => 0.0,
+            }
+        };
+        best = best.max(quality);
+        if best == 1.0 {
+            break;
+        }
+    }
+
+    best
+}
+
+fn url_host(url: &str) -> &str {
+    url.split(['/', '?', '#']).next().unwrap_or_default()
+}
+
+/// Scores `entry` against `query`, returning `None` if it doesn't match.
+fn score_entry(index: &WikiIndex, entry: &WikiEntry, query: &SearchQuery) -> Option<f32> {
+    let path = index.section_path(entry);
+    let path_lower: Vec<String> = path.iter().map(|s| s.to_lowercase()).collect();
+
+    if !query
+        .sections
+        .iter()
+        .all(|section| path_lower.iter().any(|title| title.contains(section)))
+    {
+        return None;
+    }
+
+    if !query.domains.is_empty()
+        && !entry.urls.iter().any(|url| {
+            let host = url_host(url);
+            query.domains.iter().any(|domain| {
+                host == domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+        })
+    {
+        return None;
+    }
+
+    if !query.excluded.is_empty() {
+        let haystack = format!(
+            "{} {} {}",
+            path_lower.join(" "),
+            entry.source.to_lowercase(),
+            entry.description.to_lowercase()
+        );
+        if query.excluded.iter().any(|word| haystack.contains(word)) {
+            return None;
+        }
+    }
+
+    if entry.urls.contains(&query.url) {
+        return Some(NAME_WEIGHT * query.terms.len().max(1) as f32 * 2.0);
+    }
+
+    let name_tokens = tokenize(&entry.name);
+    let heading_tokens = tokenize(&path.join(" "));
+    let description_tokens = tokenize(&entry.description);
+    let url_tokens: Vec<String> = entry.urls.iter().flat_map(|url| tokenize(url)).collect();
+
+    let mut score = 0.0;
+    for term in &query.terms {
+        let term_score = [
+            (NAME_WEIGHT, &name_tokens),
+            (HEADING_WEIGHT, &heading_tokens),
+            (DESCRIPTION_WEIGHT, &description_tokens),
+            (URL_WEIGHT, &url_tokens),
+        ]
+        .into_iter()
+        .map(|(weight, tokens)| weight * match_quality(term, tokens))
+        .fold(0.0, f32::max);
+
+        if term_score == 0.0 {
+            return None;
+        }
+        score += term_score;
+    }
+
+    if entry.starred {
+        score *= STARRED_BONUS;
+    }
+
+    Some(score)
+}
+
+/// Returns the matching entries ranked by relevance, best first.
+pub fn rank_entries<'a>(index: &'a WikiIndex, query: &SearchQuery) -> Vec<(&'a WikiEntry, f32)> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<_> = index
+        .entries
+        .iter()
+        .filter_map(|entry| score_entry(index, entry, query).map(|score| (entry, score)))
+        .collect();
+    hits.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+    hits
+}
+
+/// A formatted search hit along with the section it belongs to.
+pub struct SearchResult {
+    pub line: String,
+    pub section: String,
+}
+
+/// Searches the wiki, returning every hit formatted for an embed, best first.
+pub fn search_wiki(index: &WikiIndex, query: &str) -> Vec<SearchResult> {
+    rank_entries(index, &SearchQuery::parse(query))
+        .into_iter()
+        .map(|(entry, score)| {
+            let path = index.section_path(entry);
+            SearchResult {
+                line: format!(
+                    "- `{score:.1}` **{}** ► {}",
+                    path.join("** / **"),
+                    entry.source
+                ),
+                section: path.join(" / "),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIKI: &str = "\
+# ► Video
+
+## ▷ Streaming
+
+* ⭐ **[Cineby](https://www.cineby.app/)** - Movies / TV / Anime
+* [Flixer](https://flixer.sh/) - Movies / TV
+* [Lookmovie](https://lookmovie2.to/) - Movies / TV / NSFW
+
+## ▷ Players
+
+* [mpv](https://mpv.io/) - Video player
+
+# ► Developer Tools
+
+* [Forgejo](https://codeberg.org/forgejo/forgejo) - Self-hosted git forge
+* [Gitea](https://github.com/go-gitea/gitea) - Git service for movies
+";
+
+    fn names(query: &str) -> Vec<String> {
+        let index = WikiIndex::parse(WIKI);
+        rank_entries(&index, &SearchQuery::parse(query))
+            .into_iter()
+            .map(|(entry, _)| entry.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn parses_filters() {
+        let query = SearchQuery::parse(
+            r#"Movie  site section:"Video Streaming" domain:www.GitHub.com/ -nsfw"#,
+        );
+        assert_eq!(query.terms, ["movie", "site"]);
+        assert_eq!(query.sections, ["video streaming"]);
+        assert_eq!(query.domains, ["github.com"]);
+        assert_eq!(query.excluded, ["nsfw"]);
+    }
+
+    #[test]
+    fn matches_terms_anywhere_in_the_entry() {
+        assert_eq!(names("anime movies"), ["Cineby"]);
+        assert_eq!(names("streaming anime"), ["Cineby"]);
+        assert!(names("anime forge").is_empty());
+    }
+
+    #[test]
+    fn tolerates_typos() {
+        assert_eq!(max_distance("mpv"), 0);
+        assert_eq!(max_distance("forge"), 1);
+        assert_eq!(max_distance("forgejoo"), 2);
+        assert_eq!(names("forgjeo"), ["Forgejo"]);
+        assert_eq!(names("cinbey"), ["Cineby"]);
+        assert!(names("mvp").is_empty());
+    }
+
+    #[test]
+    fn ranks_names_above_descriptions() {
+        assert_eq!(names("gitea movies")[0], "Gitea");
+        let ranked = names("movies");
+        assert_eq!(ranked.last().map(String::as_str), Some("Gitea"));
+    }
+
+    #[test]
+    fn applies_filters() {
+        assert_eq!(
+            names("movies -nsfw section:streaming"),
+            ["Cineby", "Flixer"]
+        );
+        assert_eq!(names("domain:github.com"), ["Gitea"]);
+        assert_eq!(names("git domain:codeberg.org"), ["Forgejo"]);
+        assert_eq!(names("section:players"), ["mpv"]);
+    }
+
+    #[test]
+    fn finds_pasted_links() {
+        assert_eq!(names("https://www.mpv.io/"), ["mpv"]);
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use poise::serenity_prelude::{Context, async_trait};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use sea_orm::sqlx::types::chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use super::{WikiIndex, collect_wiki_urls};
+use crate::background_task::BackgroundTask;
+use crate::constants::FMHY_SINGLE_PAGE_ENDPOINT;
+use crate::error::Error;
+use crate::types::Data;
+use crate::url::clean_url;
+
+/// A parsed copy of the single-page wiki.
+pub struct WikiSnapshot {
+    pub content: String,
+    /// Canonical form of every link in the wiki.
+    pub urls: HashSet<String>,
+    pub index: WikiIndex,
+    /// Hex-encoded SHA-256 of `content`.
+    pub hash: String,
+    pub fetched_at: DateTime<Utc>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl WikiSnapshot {
+    fn new(content: String, etag: Option<String>, last_modified: Option<String>) -> Self {
+        let urls = collect_wiki_urls(&content)
+            .iter()
+            .map(|url| clean_url(url))
+            .collect();
+        let index = WikiIndex::parse(&content);
+        let hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+
+        Self {
+            content,
+            urls,
+            index,
+            hash,
+            fetched_at: Utc::now(),
+            etag,
+            last_modified,
+        }
+    }
+
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..8]
+    }
+
+    /// Describes the snapshot version and age, for use in message content.
+    pub fn describe(&self) -> String {
+        format!(
+            "wiki snapshot `{}` fetched <t:{}:R>",
+            self.short_hash(),
+            self.fetched_at.timestamp()
+        )
+    }
+}
+
+/// Holds the latest [`WikiSnapshot`], downloading it on first use.
+pub struct WikiSnapshotStore {
+    client: reqwest::Client,
+    current: RwLock<Option<Arc<WikiSnapshot>>>,
+    refresh_lock: Mutex<()>,
+}
+
+impl WikiSnapshotStore {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .user_agent(env!("CARGO_PKG_NAME"))
+            .build()
+            .expect("HTTP client creation failed");
+
+        Self {
+            client,
+            current: RwLock::new(None),
+            refresh_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current snapshot, fetching it if none has been loaded yet.
+    pub async fn get(&self) -> Result<Arc<WikiSnapshot>, Error> {
+        if let Some(snapshot) = self.current.read().await.clone() {
+            return Ok(snapshot);
+        }
+
+        self.refresh().await?;
+
+        Ok(self
+            .current
+            .read()
+            .await
+            .clone()
+            .expect("snapshot is set after a successful refresh"))
+    }
+
+    /// Revalidates the snapshot against the wiki, returning whether its content changed.
+    pub async fn refresh(&self) -> Result<bool, Error> {
+        let _guard = self.refresh_lock.lock().await;
+        let current = self.current.read().await.clone();
+
+        let mut request = self.client.get(FMHY_SINGLE_PAGE_ENDPOINT);
+        if let Some(current) = &current {
+            if let Some(etag) = &current.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &current.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        if current.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+
+        let response = response.error_for_status()?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+        let snapshot = WikiSnapshot::new(response.text().await?, etag, last_modified);
+
+        let changed = current.is_none_or(|c| c.hash != snapshot.hash);
+        *self.current.write().await = Some(Arc::new(snapshot));
+
+        Ok(changed)
+    }
+}
+
+impl Default for WikiSnapshotStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps [`WikiSnapshotStore`] up to date with the live wiki.
+pub struct WikiSnapshotRefresher {
+    ctx: Context,
+}
+
+impl WikiSnapshotRefresher {
+    pub fn new(ctx: Context) -> Self {
+        Self { ctx }
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for WikiSnapshotRefresher {
+    async fn init(ctx: Context) -> Result<Self, Error> {
+        Ok(Self::new(ctx))
+    }
+
+    fn interval(&mut self) -> Duration {
+        Duration::from_mins(
+            self.ctx
+                .data_ref::<Data>()
+                .config()
+                .wiki
+                .snapshot_refresh_minutes,
+        )
+    }
+
+    async fn run(&mut self) {
+        if let Err(e) = self.ctx.data_ref::<Data>().wiki.refresh().await {
+            warn!("Failed to refresh wiki snapshot: {e}");
+        }
+    }
+
+    fn timeout(&mut self) -> Option<Duration> {
+        Some(Duration::from_mins(2))
+    }
+}